@@ -124,7 +124,10 @@ async fn init_app(ctx: &mut AppCtx) -> impl FnMut(&mut AppCtx, Event) + use<> {
       })).expect("frame error");
 
       // vsync on
-      target.request_frame().expect("frame error");
+      match target.request_frame() {
+        status if status.has_frame() || status.is_skipped() => {},
+        status => panic!("frame error: {status}"),
+      }
       window.request_redraw();
 
       // statistics
@@ -0,0 +1,121 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{parse_macro_input, parse_quote, DeriveInput, Path, Data, Fields, Member, Error, token::Paren, spanned::Spanned};
+use quote::{quote, quote_spanned};
+
+
+// implements WgslLayout for #[repr(C)] structs, fails to compile if a field isn't at its WGSL offset
+// or the struct size doesn't match, #[wgsl(uniform)] additionally checks the uniform layout rules,
+// #[wgsl(crate = path)] sets the path to wgx
+#[proc_macro_derive(WgslLayout, attributes(wgsl))]
+pub fn derive_wgsl_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match wgsl_layout(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+
+fn wgsl_layout(input: DeriveInput) -> syn::Result<TokenStream2> {
+
+    let name = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(input.generics.span(), "WgslLayout can't be derived for generic structs"));
+    }
+
+    // parse attributes
+    let mut repr_c = false;
+    let mut uniform = false;
+    let mut krate: Path = parse_quote!(::wgx);
+
+    for attr in &input.attrs {
+        if attr.path().is_ident("repr") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("C") { repr_c = true }
+                // skip arguments like align(16)
+                else if meta.input.peek(Paren) {
+                    let _content; syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            })?;
+        }
+        else if attr.path().is_ident("wgsl") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("uniform") { uniform = true; Ok(()) }
+                else if meta.path.is_ident("crate") { krate = meta.value()?.parse()?; Ok(()) }
+                else { Err(meta.error("expected `uniform` or `crate`")) }
+            })?;
+        }
+    }
+
+    if !repr_c {
+        return Err(Error::new(name.span(), "WgslLayout requires #[repr(C)]"));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            Fields::Unnamed(fields) => &fields.unnamed,
+            Fields::Unit => return Err(Error::new(name.span(), "WgslLayout requires at least one field")),
+        },
+        _ => return Err(Error::new(name.span(), "WgslLayout can only be derived for structs")),
+    };
+
+    if fields.is_empty() {
+        return Err(Error::new(name.span(), "WgslLayout requires at least one field"));
+    }
+
+    let count = fields.len();
+    let mut field_layouts = Vec::with_capacity(count);
+    let mut checks = Vec::with_capacity(count);
+
+    for (i, field) in fields.iter().enumerate() {
+
+        let ty = &field.ty;
+        let (member, label) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+            None => (Member::Unnamed(i.into()), i.to_string()),
+        };
+
+        field_layouts.push(quote_spanned!{ty.span()=>
+            #krate::WgslField::of::<#ty>(::core::mem::offset_of!(#name, #member) as u64)
+        });
+
+        let offset_msg = format!("field `{label}` of `{name}` isn't at its WGSL offset, insert Pad before it");
+        checks.push(quote_spanned!{field.span()=>
+            assert!(FIELDS[#i].offset == LAYOUT.offsets[#i], #offset_msg);
+        });
+
+        if uniform {
+            let uniform_msg = format!(
+                "field `{label}` of `{name}` violates the uniform layout rules, \
+                structs and arrays need 16 byte aligned offsets, array strides and struct sizes"
+            );
+            checks.push(quote_spanned!{field.span()=>
+                assert!(LAYOUT.uniform[#i], #uniform_msg);
+            });
+        }
+    }
+
+    let size_msg = format!("size of `{name}` doesn't match its WGSL size, append Pad to the struct");
+
+    Ok(quote!{
+        const _: () = {
+            const FIELDS: [#krate::WgslField; #count] = [#(#field_layouts),*];
+            const LAYOUT: #krate::WgslStruct<#count> = #krate::WgslStruct::new(FIELDS);
+
+            impl #krate::WgslLayout for #name {
+                const ALIGN: u64 = LAYOUT.align;
+                const SIZE: u64 = LAYOUT.size;
+                const UNIFORM_ALIGN: u64 = LAYOUT.align.next_multiple_of(16);
+                const UNIFORM_SIZE: u64 = LAYOUT.size.next_multiple_of(16);
+                const UNIFORM: bool = LAYOUT.is_uniform();
+            }
+
+            #(#checks)*
+            assert!(::core::mem::size_of::<#name>() as u64 == LAYOUT.size, #size_msg);
+        };
+    })
+}
@@ -0,0 +1,156 @@
+use wgpu::{Adapter, AdapterInfo, Backends, DeviceType, DownlevelFlags, Features, Limits, PowerPreference};
+use std::fmt::Write;
+use anyhow::{Result as Res, bail};
+
+
+// helper
+fn names<T>(iter: impl Iterator<Item=(&'static str, T)>) -> String {
+    iter.map(|(name, _)| name).collect::<Vec<_>>().join(" | ")
+}
+
+
+// adapter selection policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdapterSelector {
+    pub backends: Backends,
+    pub device_types: Vec<DeviceType>, // allowed types in order of preference, empty allows any
+    pub power_preference: PowerPreference, // ranks device types if no preference is given
+    pub vendor: Option<u32>,
+    pub name: Option<String>, // case-insensitive substring of the adapter name
+    pub features: Features,
+    pub limits: Option<Limits>,
+    pub downlevel_flags: DownlevelFlags,
+}
+
+impl Default for AdapterSelector {
+    fn default() -> Self {
+        Self {
+            backends: Backends::all(),
+            device_types: Vec::new(),
+            power_preference: PowerPreference::HighPerformance,
+            vendor: None,
+            name: None,
+            features: Features::empty(),
+            limits: None,
+            downlevel_flags: DownlevelFlags::empty(),
+        }
+    }
+}
+
+impl AdapterSelector {
+
+    pub fn new() -> Self { Self::default() }
+
+    // reads WGPU_BACKEND, WGPU_POWER_PREF and WGPU_ADAPTER_NAME
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            backends: Backends::from_env().unwrap_or(default.backends),
+            power_preference: PowerPreference::from_env().unwrap_or(default.power_preference),
+            name: std::env::var("WGPU_ADAPTER_NAME").ok(),
+            ..default
+        }
+    }
+
+    pub fn backends(mut self, backends: Backends) -> Self { self.backends = backends; self }
+    pub fn device_types(mut self, device_types: &[DeviceType]) -> Self { self.device_types = device_types.to_vec(); self }
+    pub fn power_preference(mut self, power_preference: PowerPreference) -> Self { self.power_preference = power_preference; self }
+    pub fn vendor(mut self, vendor: u32) -> Self { self.vendor = Some(vendor); self }
+    pub fn name(mut self, name: impl Into<String>) -> Self { self.name = Some(name.into()); self }
+    pub fn features(mut self, features: Features) -> Self { self.features = features; self }
+    pub fn limits(mut self, limits: Limits) -> Self { self.limits = Some(limits); self }
+    pub fn downlevel_flags(mut self, flags: DownlevelFlags) -> Self { self.downlevel_flags = flags; self }
+
+
+    // reasons why an adapter is rejected, empty if it is accepted
+    pub fn rejections(&self, adapter: &Adapter, compatible_surface: Option<&wgpu::Surface>) -> Vec<String> {
+
+        let info = adapter.get_info();
+        let mut reasons = Vec::new();
+
+        if !self.backends.contains(info.backend.into()) {
+            reasons.push(format!("backend {:?} not allowed", info.backend));
+        }
+
+        if !self.device_types.is_empty() && !self.device_types.contains(&info.device_type) {
+            reasons.push(format!("device type {:?} not allowed", info.device_type));
+        }
+
+        if let Some(vendor) = self.vendor && vendor != info.vendor {
+            reasons.push(format!("vendor {:#06x} doesn't match {vendor:#06x}", info.vendor));
+        }
+
+        if let Some(name) = &self.name && !info.name.to_lowercase().contains(&name.to_lowercase()) {
+            reasons.push(format!("name doesn't contain '{name}'"));
+        }
+
+        let missing = self.features.difference(adapter.features());
+        if !missing.is_empty() {
+            reasons.push(format!("missing features {}", names(missing.iter_names())));
+        }
+
+        if let Some(limits) = &self.limits {
+            limits.check_limits_with_fail_fn(&adapter.limits(), false, |name, requested, allowed| {
+                reasons.push(format!("limit {name} is {allowed}, requested {requested}"));
+            });
+        }
+
+        let missing = self.downlevel_flags.difference(adapter.get_downlevel_capabilities().flags);
+        if !missing.is_empty() {
+            reasons.push(format!("missing downlevel flags {}", names(missing.iter_names())));
+        }
+
+        if let Some(surface) = compatible_surface && !adapter.is_surface_supported(surface) {
+            reasons.push("surface not supported".to_string());
+        }
+
+        reasons
+    }
+
+    // lower is better
+    fn rank(&self, info: &AdapterInfo) -> usize {
+        use DeviceType::*;
+
+        if !self.device_types.is_empty() {
+            return self.device_types.iter().position(|ty| *ty == info.device_type).unwrap_or(usize::MAX);
+        }
+
+        let order: &[DeviceType] = match self.power_preference {
+            PowerPreference::HighPerformance => &[DiscreteGpu, IntegratedGpu, VirtualGpu, Other, Cpu],
+            PowerPreference::LowPower => &[IntegratedGpu, DiscreteGpu, VirtualGpu, Other, Cpu],
+            PowerPreference::None => return 0, // keep enumeration order
+        };
+
+        order.iter().position(|ty| *ty == info.device_type).unwrap_or(order.len())
+    }
+
+
+    pub fn select_from(&self, adapters: impl IntoIterator<Item=Adapter>, compatible_surface: Option<&wgpu::Surface>) -> Res<Adapter> {
+
+        let mut accepted = Vec::new();
+        let mut rejected = String::new();
+
+        for adapter in adapters {
+            let reasons = self.rejections(&adapter, compatible_surface);
+            if reasons.is_empty() {
+                accepted.push(adapter);
+            } else {
+                let info = adapter.get_info();
+                write!(rejected, "\n  {} ({:?}, {:?}): {}", info.name, info.backend, info.device_type, reasons.join(", "))?;
+            }
+        }
+
+        accepted.sort_by_cached_key(|adapter| self.rank(&adapter.get_info()));
+
+        match accepted.into_iter().next() {
+            Some(adapter) => Ok(adapter),
+            None if rejected.is_empty() => bail!("no adapters available for backends {:?}", self.backends),
+            None => bail!("no suitable adapter found, rejected:{rejected}"),
+        }
+    }
+
+    pub async fn select(&self, instance: &wgpu::Instance, compatible_surface: Option<&wgpu::Surface<'_>>) -> Res<Adapter> {
+        let adapters = instance.enumerate_adapters(self.backends).await;
+        self.select_from(adapters, compatible_surface)
+    }
+}
@@ -0,0 +1,183 @@
+use std::ops::{Range, RangeBounds};
+use wgpu::{Buffer, BufferAddress, BufferSize, BufferSlice, BufferBinding, COPY_BUFFER_ALIGNMENT};
+use crate::{*};
+use anyhow::{Result as Res, bail};
+
+
+// part of a shared buffer, not Clone so it can only be freed once
+
+#[derive(Debug)]
+pub struct Allocation {
+  buffer: Buffer,
+  range: Range<BufferAddress>,
+  block: usize,
+}
+
+impl Allocation {
+
+  pub fn buffer(&self) -> &Buffer { &self.buffer }
+
+  // byte range in the buffer
+  pub fn range(&self) -> Range<BufferAddress> { self.range.clone() }
+
+  pub fn offset(&self) -> BufferAddress { self.range.start }
+
+  pub fn size(&self) -> BufferAddress { self.range.end - self.range.start }
+
+  // relative to the allocation start, absolute in the buffer
+  pub fn sub_range(&self, range: impl RangeBounds<BufferAddress>) -> Res<Range<BufferAddress>> {
+    let range = range.map_into(0..self.size())?;
+    Ok((range.start + self.range.start)..(range.end + self.range.start))
+  }
+
+  pub fn slice(&self) -> BufferSlice<'_> { self.buffer.slice(self.range.clone()) }
+
+  pub fn binding(&self) -> BufferBinding<'_> {
+    BufferBinding { buffer: &self.buffer, offset: self.range.start, size: BufferSize::new(self.size()) }
+  }
+
+  pub fn write<T: ReadBytes>(&self, gx: &impl WgxQueue, offset: BufferAddress, data: T) -> Res<()> {
+    let range = self.sub_range(offset..(offset + data.read_bytes().len() as u64))?;
+    gx.write_buffer(&self.buffer, range.start, data);
+    Ok(())
+  }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AllocatorStats {
+  pub blocks: usize,
+  pub allocations: usize,
+  pub capacity: u64,
+  pub allocated: u64,
+  pub free_ranges: usize,
+  pub largest_free: u64,
+}
+
+impl AllocatorStats {
+
+  pub fn free(&self) -> u64 { self.capacity - self.allocated }
+
+  // 0 if all free space is one range, approaches 1 with many small free ranges
+  pub fn fragmentation(&self) -> f32 {
+    if self.free() == 0 { 0.0 } else { 1.0 - self.largest_free as f32 / self.free() as f32 }
+  }
+}
+
+
+#[derive(Debug)]
+struct Block {
+  buffer: Buffer,
+  free: RangeSet<BufferAddress>,
+  allocations: usize,
+}
+
+impl Block {
+
+  // best fit
+  fn allocate(&mut self, size: BufferAddress) -> Option<Range<BufferAddress>> {
+    let free = self.free.ranges().iter()
+      .filter(|free| free.end - free.start >= size)
+      .min_by_key(|free| free.end - free.start)?;
+
+    let range = free.start..(free.start + size);
+    self.free.remove(range.clone());
+    self.allocations += 1;
+
+    Some(range)
+  }
+}
+
+
+// sub allocates ranges from a few large buffers, offsets and sizes are aligned to
+// min_storage_buffer_offset_alignment by default, so allocations can be bound directly
+
+#[derive(Debug)]
+pub struct BufferAllocator {
+  usage: BufUse,
+  block_size: BufferAddress,
+  alignment: BufferAddress,
+  blocks: Vec<Option<Block>>,
+}
+
+impl BufferAllocator {
+
+  pub fn new(gx: &impl WgxDevice, usage: BufUse, block_size: BufferAddress) -> Self {
+    let alignment = (gx.device().limits().min_storage_buffer_offset_alignment as u64).max(COPY_BUFFER_ALIGNMENT);
+    Self { usage, block_size, alignment, blocks: Vec::new() }
+  }
+
+  pub fn alignment(mut self, alignment: BufferAddress) -> Self {
+    assert!(alignment.is_power_of_two(), "alignment has to be a power of two");
+    self.alignment = alignment.max(COPY_BUFFER_ALIGNMENT);
+    self
+  }
+
+  pub fn allocate(&mut self, gx: &impl WgxDevice, size: BufferAddress) -> Res<Allocation> {
+
+    let size = size.max(1).next_multiple_of(self.alignment);
+
+    for (block, slot) in self.blocks.iter_mut().enumerate() {
+      if let Some(slot) = slot && let Some(range) = slot.allocate(size) {
+        return Ok(Allocation { buffer: slot.buffer.clone(), range, block });
+      }
+    }
+
+    // larger allocations get a block of their own
+    let block_size = self.block_size.next_multiple_of(self.alignment).max(size);
+    let max_size = gx.device().limits().max_buffer_size;
+
+    if block_size > max_size {
+      bail!("allocation of {size} bytes exceeds the max buffer size {max_size}");
+    }
+
+    let mut new_block = Block {
+      buffer: gx.buffer(self.usage, block_size, false),
+      free: RangeSet::new(),
+      allocations: 0,
+    };
+    new_block.free.insert(0..block_size);
+
+    let range = new_block.allocate(size).unwrap();
+    let buffer = new_block.buffer.clone();
+
+    // reuse released slots, so block indices stay valid
+    let block = match self.blocks.iter().position(Option::is_none) {
+      Some(block) => { self.blocks[block] = Some(new_block); block },
+      None => { self.blocks.push(Some(new_block)); self.blocks.len() - 1 },
+    };
+
+    Ok(Allocation { buffer, range, block })
+  }
+
+  pub fn free(&mut self, allocation: Allocation) {
+    let block = self.blocks.get_mut(allocation.block).and_then(Option::as_mut).expect("allocation from another allocator");
+    assert!(block.buffer == allocation.buffer, "allocation from another allocator");
+    block.free.insert(allocation.range);
+    block.allocations -= 1;
+  }
+
+  // releases blocks without allocations
+  pub fn trim(&mut self) {
+    for slot in &mut self.blocks {
+      if slot.as_ref().is_some_and(|block| block.allocations == 0) { *slot = None }
+    }
+    while self.blocks.last().is_some_and(Option::is_none) { self.blocks.pop(); }
+  }
+
+  pub fn stats(&self) -> AllocatorStats {
+    let mut stats = AllocatorStats::default();
+
+    for block in self.blocks.iter().flatten() {
+      let free: u64 = block.free.ranges().iter().map(|free| free.end - free.start).sum();
+      stats.blocks += 1;
+      stats.allocations += block.allocations;
+      stats.capacity += block.buffer.size();
+      stats.allocated += block.buffer.size() - free;
+      stats.free_ranges += block.free.ranges().len();
+      stats.largest_free = block.free.ranges().iter().map(|free| free.end - free.start).fold(stats.largest_free, u64::max);
+    }
+
+    stats
+  }
+}
@@ -69,6 +69,62 @@ impl<T: Step + fmt::Debug, R: RangeBounds<T>> MapIntoRange<T> for R {
 }
 
 
+// sorted, non overlapping and non adjacent ranges
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeSet<T> {
+  ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for RangeSet<T> {
+  fn default() -> Self { Self { ranges: Vec::new() } }
+}
+
+impl<T: Ord + Copy> RangeSet<T> {
+
+  pub fn new() -> Self { Self::default() }
+
+  pub fn ranges(&self) -> &[Range<T>] { &self.ranges }
+
+  pub fn is_empty(&self) -> bool { self.ranges.is_empty() }
+
+  pub fn clear(&mut self) { self.ranges.clear() }
+
+  pub fn drain(&mut self) -> impl Iterator<Item=Range<T>> + '_ { self.ranges.drain(..) }
+
+  // merges with overlapping and adjacent ranges
+  pub fn insert(&mut self, range: Range<T>) {
+    if range.is_empty() { return }
+
+    let i = self.ranges.partition_point(|r| r.end < range.start);
+    let j = self.ranges.partition_point(|r| r.start <= range.end);
+
+    if i == j {
+      self.ranges.insert(i, range);
+    } else {
+      let merged = range.start.min(self.ranges[i].start)..range.end.max(self.ranges[j-1].end);
+      self.ranges.splice(i..j, [merged]);
+    }
+  }
+
+  // splits overlapping ranges
+  pub fn remove(&mut self, range: Range<T>) {
+    if range.is_empty() { return }
+
+    let i = self.ranges.partition_point(|r| r.end <= range.start);
+    let j = self.ranges.partition_point(|r| r.start < range.end);
+
+    if i == j { return }
+
+    let (first, last) = (self.ranges[i].start, self.ranges[j-1].end);
+    let left = (first < range.start).then_some(first..range.start);
+    let right = (range.end < last).then_some(range.end..last);
+
+    self.ranges.splice(i..j, left.into_iter().chain(right));
+  }
+}
+
+
 // convert to byte ranges
 
 pub const fn byte_range<T>(data_range: Range<usize>) -> Range<usize> {
@@ -184,4 +240,36 @@ impl DrawIndexedIndirectRanges for DrawIndexedIndirectArgs {
   fn instance_range(&self) -> Res<Range<u32>> {
     Ok(self.first_instance..self.first_instance.checked_add(self.instance_count).context("DrawIndexedIndirect instance range overflow")?)
   }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn range_set() {
+    let mut set = RangeSet::new();
+
+    set.insert(4..6);
+    set.insert(0..1);
+    set.insert(8..10);
+    assert_eq!(set.ranges(), [0..1, 4..6, 8..10]);
+
+    // adjacent and overlapping ranges merge
+    set.insert(6..8);
+    assert_eq!(set.ranges(), [0..1, 4..10]);
+
+    set.insert(1..5);
+    set.insert(3..3);
+    assert_eq!((set.ranges().len(), &set.ranges()[0]), (1, &(0..10)));
+
+    // removing splits
+    set.remove(2..4);
+    set.remove(9..12);
+    assert_eq!(set.ranges(), [0..2, 4..9]);
+
+    set.remove(0..5);
+    assert_eq!((set.ranges().len(), &set.ranges()[0]), (1, &(5..9)));
+  }
+}
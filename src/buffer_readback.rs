@@ -0,0 +1,192 @@
+use std::{fmt, marker::PhantomData, ops::{Range, RangeBounds}, pin::Pin, sync::{Arc, Mutex}, task::{Context, Poll, Waker}};
+use wgpu::{Buffer, BufferAddress, BufferSlice, CommandEncoder, COPY_BUFFER_ALIGNMENT};
+use crate::{*};
+use anyhow::{Result as Res, bail};
+
+
+// buffer copies need aligned offsets and sizes
+pub(crate) fn check_copy_range(source: &Buffer, range: &Range<BufferAddress>) -> Res<()> {
+  if !source.usage().contains(BufUse::COPY_SRC) {
+    bail!("buffer needs COPY_SRC usage to be read back");
+  }
+  if range.is_empty() {
+    bail!("readback range can't be empty");
+  }
+  if !range.start.is_multiple_of(COPY_BUFFER_ALIGNMENT) || !(range.end - range.start).is_multiple_of(COPY_BUFFER_ALIGNMENT) {
+    bail!("readback range {range:?} is not aligned to {COPY_BUFFER_ALIGNMENT} bytes");
+  }
+  Ok(())
+}
+
+// MAP_READ buffers are mapped directly, others are copied to a staging buffer
+fn stage_readback(gx: &impl WgxDeviceQueue, slice: BufferSlice) -> Res<(Buffer, Range<BufferAddress>)> {
+  let source = slice.buffer();
+  let range = slice.offset()..(slice.offset() + slice.size().get());
+
+  if source.usage().contains(BufUse::MAP_READ) {
+    return Ok((source.clone(), range));
+  }
+
+  check_copy_range(source, &range)?;
+
+  let size = range.end - range.start;
+  let staging = gx.buffer(BufUse::MAP_READ | BufUse::COPY_DST, size, false);
+  gx.with_encoder(|encoder| encoder.copy_buffer_to_buffer(source, range.start, &staging, 0, size));
+
+  Ok((staging, 0..size))
+}
+
+
+// copies are submitted and the mapping requested immediately,
+// on native the device has to be polled for the future to resolve
+pub fn read_buffer<G: WgxDeviceQueue>(gx: &G, slice: BufferSlice) -> impl Future<Output=Res<Vec<u8>>> + use<G> {
+
+  let mapped = stage_readback(gx, slice).map(|(buffer, range)| {
+    let mapped = map_async(&buffer.slice(range.clone()), MapMode::Read);
+    (buffer, range, mapped)
+  });
+
+  async move {
+    let (buffer, range, mapped) = mapped?;
+    mapped.await?;
+    let data = buffer.slice(range).get_mapped_range().to_vec();
+    buffer.unmap();
+    Ok(data)
+  }
+}
+
+
+// result delivered by a ReadbackQueue
+
+type ReadbackState = Arc<Mutex<(Option<Res<Vec<u8>>>, Option<Waker>)>>;
+
+#[derive(Debug)]
+pub struct ReadbackFuture<T> {
+  state: ReadbackState,
+  _type: PhantomData<fn() -> T>,
+}
+
+impl<T> ReadbackFuture<T> {
+
+  // the future and the callback resolving it
+  pub fn channel() -> (Self, impl FnOnce(Res<Vec<u8>>) + Send + 'static) {
+    let state = ReadbackState::default();
+    let callback_state = state.clone();
+
+    let callback = move |result| {
+      let (res, waker) = &mut *callback_state.lock().unwrap();
+      *res = Some(result);
+      if let Some(waker) = waker.take() { waker.wake() }
+    };
+
+    (Self { state, _type: PhantomData }, callback)
+  }
+}
+
+impl<T: AsBytes> Future for ReadbackFuture<T> {
+  type Output = Res<Vec<T>>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+    let (res, waker) = &mut *self.state.lock().unwrap();
+    match res.take() {
+      Some(result) => Poll::Ready(result.map(|bytes| pod_collect_to_vec(&bytes))),
+      None => { *waker = Some(cx.waker().clone()); Poll::Pending },
+    }
+  }
+}
+
+
+pub(crate) type ReadbackCallback = Box<dyn FnOnce(Res<Vec<u8>>) + Send>;
+pub(crate) type MapFuture = Pin<Box<dyn Future<Output=Res<()>> + Send>>;
+
+
+// collects buffer readbacks, poll it once per frame:
+// recorded copies are submitted together, finished mappings are delivered without blocking
+
+#[derive(Default)]
+pub struct ReadbackQueue {
+  encoder: Option<CommandEncoder>,
+  recorded: Vec<(Buffer, ReadbackCallback)>,
+  mapping: Vec<(Buffer, MapFuture, ReadbackCallback)>,
+}
+
+impl fmt::Debug for ReadbackQueue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("ReadbackQueue")
+      .field("recorded", &self.recorded.len())
+      .field("mapping", &self.mapping.len())
+      .finish()
+  }
+}
+
+impl ReadbackQueue {
+
+  pub fn new() -> Self { Self::default() }
+
+  pub fn pending(&self) -> usize { self.recorded.len() + self.mapping.len() }
+
+  pub fn enqueue(
+    &mut self, gx: &impl WgxDevice, source: &Buffer, range: impl RangeBounds<BufferAddress>,
+    callback: impl FnOnce(Res<Vec<u8>>) + Send + 'static,
+  ) -> Res<()> {
+
+    let range = range.map_into(0..source.size())?;
+    check_copy_range(source, &range)?;
+
+    let size = range.end - range.start;
+    let staging = gx.buffer(BufUse::MAP_READ | BufUse::COPY_DST, size, false);
+
+    self.encoder.get_or_insert_with(|| gx.command_encoder()).copy_buffer_to_buffer(source, range.start, &staging, 0, size);
+    self.recorded.push((staging, Box::new(callback)));
+
+    Ok(())
+  }
+
+  pub fn read(&mut self, gx: &impl WgxDevice, source: &Buffer, range: impl RangeBounds<BufferAddress>) -> Res<ReadbackFuture<u8>> {
+    let (future, callback) = ReadbackFuture::channel();
+    self.enqueue(gx, source, range, callback)?;
+    Ok(future)
+  }
+
+  // range in elements
+  pub fn read_typed<T: AsBytes>(&mut self, gx: &impl WgxDevice, source: &TypedBuffer<T>, range: impl RangeBounds<usize>)
+    -> Res<ReadbackFuture<T>>
+  {
+    let (future, callback) = ReadbackFuture::channel();
+    self.enqueue(gx, &source.buffer, source.byte_range(range)?, callback)?;
+    Ok(future)
+  }
+
+  // submits recorded copies, polls the device without blocking and delivers finished readbacks,
+  // returns the number of readbacks still pending
+  pub fn poll(&mut self, gx: &impl WgxDeviceQueue) -> Res<usize> {
+
+    if let Some(encoder) = self.encoder.take() {
+      gx.queue().submit([encoder.finish()]);
+
+      for (buffer, callback) in self.recorded.drain(..) {
+        let mapped = Box::pin(map_async(&buffer.slice(..), MapMode::Read));
+        self.mapping.push((buffer, mapped, callback));
+      }
+    }
+
+    gx.device().poll(wgpu::PollType::Poll)?;
+
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut i = 0;
+
+    while i < self.mapping.len() {
+      if let Poll::Ready(result) = self.mapping[i].1.as_mut().poll(&mut cx) {
+        let (buffer, _, callback) = self.mapping.remove(i);
+        callback(result.map(|()| {
+          let data = buffer.slice(..).get_mapped_range().to_vec();
+          buffer.unmap();
+          data
+        }));
+      }
+      else { i += 1 }
+    }
+
+    Ok(self.pending())
+  }
+}
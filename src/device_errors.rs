@@ -0,0 +1,194 @@
+use std::{sync::{Arc, Mutex}, mem};
+use wgpu::{DeviceLostReason, ErrorFilter};
+use crate::*;
+use anyhow::{Result as Res, anyhow, bail};
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceLost {
+    pub reason: DeviceLostReason,
+    pub message: String,
+}
+
+
+#[derive(Debug, Default)]
+struct ErrorState {
+    errors: Vec<wgpu::Error>,
+    lost: Option<DeviceLost>,
+}
+
+// collects uncaptured errors and device loss of a device
+#[derive(Debug, Clone, Default)]
+pub struct DeviceErrors {
+    state: Arc<Mutex<ErrorState>>,
+}
+
+impl DeviceErrors {
+
+    pub fn install(device: &wgpu::Device) -> Self {
+
+        let errors = Self::default();
+
+        let state = errors.state.clone();
+        device.on_uncaptured_error(Arc::new(move |error| {
+            log::error!("uncaptured wgpu error: {error}");
+            state.lock().unwrap().errors.push(error);
+        }));
+
+        let state = errors.state.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            log::error!("device lost ({reason:?}): {message}");
+            state.lock().unwrap().lost = Some(DeviceLost { reason, message });
+        });
+
+        errors
+    }
+
+    pub fn has_errors(&self) -> bool { !self.state.lock().unwrap().errors.is_empty() }
+
+    pub fn take_errors(&self) -> Vec<wgpu::Error> {
+        mem::take(&mut self.state.lock().unwrap().errors)
+    }
+
+    pub fn lost(&self) -> Option<DeviceLost> { self.state.lock().unwrap().lost.clone() }
+
+    pub fn is_lost(&self) -> bool { self.state.lock().unwrap().lost.is_some() }
+
+    // fails with all collected errors, clearing them
+    pub fn check(&self) -> Res<()> {
+        let ErrorState { errors, lost } = &mut *self.state.lock().unwrap();
+
+        if let Some(DeviceLost { reason, message }) = lost {
+            bail!("device lost ({reason:?}): {message}");
+        }
+
+        if !errors.is_empty() {
+            let messages: Vec<_> = errors.drain(..).map(|err| err.to_string()).collect();
+            bail!("{} uncaptured error(s):\n{}", messages.len(), messages.join("\n"));
+        }
+
+        Ok(())
+    }
+}
+
+
+// error scope
+#[must_use = "error scopes must be popped to retrieve their errors"]
+pub struct ErrorScope(wgpu::ErrorScopeGuard);
+
+impl ErrorScope {
+    pub fn pop(self) -> impl Future<Output=Res<()>> {
+        let popped = self.0.pop(); // takes effect immediately
+        async move {
+            match popped.await {
+                Some(error) => Err(anyhow!(error)),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+pub trait ErrorScopeExtension: WgxDevice {
+
+    fn push_error_scope(&self, filter: ErrorFilter) -> ErrorScope {
+        ErrorScope(self.device().push_error_scope(filter))
+    }
+
+    fn with_error_scope<T>(&self, filter: ErrorFilter, handler: impl FnOnce() -> T) -> impl Future<Output=Res<T>> {
+        let scope = ErrorScopeExtension::push_error_scope(self, filter);
+        let res = handler();
+        let popped = scope.pop();
+        async move { popped.await.map(|_| res) }
+    }
+}
+
+impl<T: WgxDevice> ErrorScopeExtension for T {}
+
+
+// device recreation
+
+// everything created from the old device (buffers, textures, views, samplers, bind groups, shaders,
+// pipelines, render bundles) has to be recreated, surfaces have to be reconfigured
+#[derive(Debug, Clone)]
+pub struct DeviceRecreated {
+    pub lost: Option<DeviceLost>,
+    pub adapter_changed: bool,
+    pub missing_features: wgpu::Features, // previously available features
+    pub recreated: Vec<&'static str>, // type names of the resources recreated through this report
+}
+
+impl DeviceRecreated {
+    // recreates a resource on the new device and records it
+    pub fn recreate<T: Recreate>(&mut self, gx: &impl WgxDevice, resource: &mut T) -> &mut Self {
+        resource.recreate(gx);
+        self.recreated.push(std::any::type_name::<T>());
+        self
+    }
+}
+
+// resources that can rebuild themselves on a new device, contents are not preserved
+pub trait Recreate {
+    fn recreate(&mut self, gx: &impl WgxDevice);
+}
+
+impl Wgx {
+
+    // requests a new device on the current adapter, if that fails the selector picks a new adapter
+    // compatible with the given surface, the old device is destroyed only after success
+    pub async fn recreate_device(
+        &mut self, features: wgpu::Features, limits: wgpu::Limits,
+        selector: &AdapterSelector, compatible_surface: Option<&wgpu::Surface<'_>>,
+    )
+        -> Res<DeviceRecreated>
+    {
+        let lost = self.errors.lost();
+        let old_features = self.device.features();
+
+        let (adapter, device, queue) = match Self::request_device(&self.adapter, features, limits.clone()).await {
+            Ok((device, queue)) => (self.adapter.clone(), device, queue),
+            Err(err) => {
+                log::warn!("recreating device on the same adapter failed: {err:?}, selecting new adapter");
+                let adapter = selector.select(&self.instance, compatible_surface).await?;
+                let (device, queue) = Self::request_device(&adapter, features, limits).await?;
+                (adapter, device, queue)
+            },
+        };
+
+        let adapter_changed = adapter.get_info() != self.adapter.get_info();
+
+        self.device.destroy();
+        *self = Self::from_parts(self.instance.clone(), adapter, device, queue);
+
+        Ok(DeviceRecreated {
+            lost, adapter_changed,
+            missing_features: old_features.difference(self.device.features()),
+            recreated: Vec::new(),
+        })
+    }
+}
+
+
+impl Recreate for TextureLot {
+    fn recreate(&mut self, gx: &impl WgxDevice) {
+        *self = Self::new(gx, self.descriptor);
+    }
+}
+
+impl Recreate for TextureTarget {
+    fn recreate(&mut self, gx: &impl WgxDevice) {
+        self.resize(gx, self.size());
+    }
+}
+
+impl Recreate for SurfaceTarget {
+    fn recreate(&mut self, gx: &impl WgxDevice) {
+        self.device = gx.device().clone();
+        self.configure(gx, self.depth_testing());
+    }
+}
+
+impl<const N: usize> Recreate for MrtTarget<N> {
+    fn recreate(&mut self, gx: &impl WgxDevice) {
+        self.resize(gx, self.size());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::*;
+use wgpu::{Adapter, Features, Limits, DownlevelCapabilities};
+use anyhow::{Result as Res, anyhow, bail};
+
+
+// device request with required and optional capabilities
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceRequest {
+    pub required_features: Features,
+    pub optional_features: Features,
+    pub required_limits: Limits, // minimums, fail if not supported
+    pub preferred_limits: Option<Limits>, // clamped to what the adapter supports
+}
+
+impl DeviceRequest {
+
+    pub fn new(required_features: Features, required_limits: Limits) -> Self {
+        Self { required_features, optional_features: Features::empty(), required_limits, preferred_limits: None }
+    }
+
+    pub fn optional_features(mut self, features: Features) -> Self { self.optional_features = features; self }
+    pub fn preferred_limits(mut self, limits: Limits) -> Self { self.preferred_limits = Some(limits); self }
+
+    // negotiate with the adapter, returns features and limits to request
+    pub fn negotiate(&self, adapter: &Adapter) -> Res<(Features, Limits)> {
+
+        let adapter_features = adapter.features();
+        let adapter_limits = adapter.limits();
+
+        let mut errors = Vec::new();
+
+        let missing = self.required_features.difference(adapter_features);
+        if !missing.is_empty() {
+            errors.push(format!("missing features {missing}"));
+        }
+
+        self.required_limits.check_limits_with_fail_fn(&adapter_limits, false, |name, requested, allowed| {
+            errors.push(format!("limit {name} is {allowed}, required {requested}"));
+        });
+
+        if !errors.is_empty() {
+            bail!("adapter '{}' doesn't meet the requirements: {}", adapter.get_info().name, errors.join(", "));
+        }
+
+        let features = self.required_features | self.optional_features.intersection(adapter_features);
+
+        let limits = match &self.preferred_limits {
+            Some(preferred) => preferred.clone().or_worse_values_from(&adapter_limits).or_better_values_from(&self.required_limits),
+            None => self.required_limits.clone(),
+        };
+
+        Ok((features, limits))
+    }
+}
+
+impl From<(Features, Limits)> for DeviceRequest {
+    fn from((features, limits): (Features, Limits)) -> Self { Self::new(features, limits) }
+}
+
+
+// what was actually granted
+#[derive(Debug, Clone)]
+pub struct DeviceCaps {
+    pub features: Features,
+    pub missing_features: Features, // optional features that were not granted
+    pub limits: Limits,
+    pub downlevel: DownlevelCapabilities,
+}
+
+impl DeviceCaps {
+    pub fn has(&self, features: Features) -> bool { self.features.contains(features) }
+}
+
+
+impl Wgx {
+
+    pub async fn request_negotiated_device(adapter: &Adapter, request: &DeviceRequest)
+        -> Res<(wgpu::Device, wgpu::Queue, DeviceCaps)>
+    {
+        let (required_features, required_limits) = request.negotiate(adapter)?;
+
+        let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+            label: None,
+            required_features,
+            required_limits,
+            memory_hints: Default::default(),
+            trace: Default::default(),
+            experimental_features: Default::default(),
+        }).await.map_err(|err| anyhow!("{err:?}"))?;
+
+        let features = device.features();
+
+        let caps = DeviceCaps {
+            missing_features: request.optional_features.difference(features),
+            features,
+            limits: device.limits(),
+            downlevel: adapter.get_downlevel_capabilities(),
+        };
+
+        Ok((device, queue, caps))
+    }
+
+    pub async fn new_negotiated<W: Into<wgpu::SurfaceTarget<'static>>>(
+        window:Option<W>, selector:&AdapterSelector, request:&DeviceRequest,
+    )
+        -> Res<(Self, Option<wgpu::Surface<'static>>, DeviceCaps)>
+    {
+        let instance = Self::instance();
+        let (adapter, surface) = Self::select_adapter(&instance, window, selector).await?;
+        let (device, queue, caps) = Self::request_negotiated_device(&adapter, request).await?;
+        Ok((Self::from_parts(instance, adapter, device, queue), surface, caps))
+    }
+}
@@ -0,0 +1,144 @@
+use std::ops::{Range, RangeBounds, Deref};
+use wgpu::{BufferAddress, COPY_BUFFER_ALIGNMENT};
+use crate::{*};
+
+
+// size in bytes aligned for buffer copies
+fn aligned_size<T>(len: usize) -> BufferAddress {
+  buffer_range::<T>(0..len.max(1)).end.next_multiple_of(COPY_BUFFER_ALIGNMENT)
+}
+
+
+// vector with a cpu shadow mirrored into a gpu buffer,
+// changes are tracked and only dirty ranges are uploaded on flush
+
+#[derive(Debug)]
+pub struct GpuVec<T: AsBytes> {
+  data: Vec<T>,
+  buffer: TypedBuffer<T>,
+  dirty: RangeSet<usize>,
+  generation: u64,
+}
+
+impl<T: AsBytes> GpuVec<T> {
+
+  // the buffer gets COPY_SRC and COPY_DST usage additionally
+  pub fn new(gx: &impl WgxDevice, usage: BufUse, capacity: usize) -> Self {
+    let usage = usage | BufUse::COPY_SRC | BufUse::COPY_DST;
+    Self {
+      data: Vec::with_capacity(capacity),
+      buffer: TypedBuffer::from_buffer(gx.buffer(usage, aligned_size::<T>(capacity), false)),
+      dirty: RangeSet::default(),
+      generation: 0,
+    }
+  }
+
+  pub fn from_vec(gx: &impl WgxDevice, usage: BufUse, data: Vec<T>) -> Self {
+    let usage = usage | BufUse::COPY_SRC | BufUse::COPY_DST;
+    Self {
+      buffer: TypedBuffer::from_buffer(gx.buffer_from_data(usage, data.as_slice())),
+      data,
+      dirty: RangeSet::default(),
+      generation: 0,
+    }
+  }
+
+  pub fn buffer(&self) -> &TypedBuffer<T> { &self.buffer }
+
+  pub fn capacity(&self) -> usize { self.buffer.len() }
+
+  pub fn as_slice(&self) -> &[T] { &self.data }
+
+  pub fn is_dirty(&self) -> bool { !self.dirty.is_empty() || self.data.len() > self.capacity() }
+
+  // changes whenever the buffer is reallocated, bind groups have to be rebuilt then
+  pub fn generation(&self) -> u64 { self.generation }
+
+  pub fn mark_dirty(&mut self, range: impl RangeBounds<usize>) {
+    if let Ok(range) = range.map_into(0..self.data.len()) {
+      self.dirty.insert(range);
+    }
+  }
+
+  pub fn push(&mut self, value: T) -> usize {
+    let index = self.data.len();
+    self.data.push(value);
+    self.dirty.insert(index..index+1);
+    index
+  }
+
+  pub fn extend_from_slice(&mut self, source: &[T]) -> Range<usize> {
+    self.copy_extend(source, None)
+  }
+
+  // overwrites from offset and extends if needed, see CopyExtend
+  pub fn copy_extend(&mut self, source: &[T], offset: Option<usize>) -> Range<usize> {
+    let range = self.data.copy_extend(source, offset);
+    self.dirty.insert(range.clone());
+    range
+  }
+
+  pub fn set(&mut self, index: usize, value: T) {
+    self.data[index] = value;
+    self.dirty.insert(index..index+1);
+  }
+
+  pub fn slice_mut(&mut self, range: impl RangeBounds<usize>) -> &mut [T] {
+    let range = range.map_into(0..self.data.len()).expect("range out of bounds");
+    self.dirty.insert(range.clone());
+    &mut self.data[range]
+  }
+
+  // the buffer keeps its capacity
+  pub fn truncate(&mut self, len: usize) {
+    self.data.truncate(len);
+  }
+
+  pub fn clear(&mut self) {
+    self.data.clear();
+    self.dirty.clear();
+  }
+
+  // records uploads of dirty ranges, returns true if the buffer was reallocated
+  pub fn flush(&mut self, gx: &impl WgxDevice, staging: &mut StagingEncoder) -> bool {
+
+    let mut reallocated = false;
+
+    if self.data.len() > self.capacity() {
+      let capacity = self.data.len().max(self.capacity() * 2);
+      let buffer = gx.buffer(self.buffer.usage(), aligned_size::<T>(capacity), false);
+
+      // keep the uploaded contents, dirty ranges are written afterwards
+      staging.encoder.copy_buffer_to_buffer(&self.buffer.buffer, 0, &buffer, 0, self.buffer.buffer.size());
+
+      self.buffer = TypedBuffer::from_buffer(buffer);
+      self.generation += 1;
+      reallocated = true;
+    }
+
+    let bytes: &[u8] = cast_slice(&self.data);
+
+    for range in self.dirty.drain() {
+      let range = buffer_range::<T>(range.start..range.end.min(self.data.len()));
+      if range.is_empty() { continue }
+
+      // copies need aligned offsets and sizes, the buffer size is aligned already
+      let start = range.start - range.start % COPY_BUFFER_ALIGNMENT;
+      let end = range.end.next_multiple_of(COPY_BUFFER_ALIGNMENT);
+
+      let (start, end, available) = (start as usize, end as usize, bytes.len().min(end as usize));
+
+      let mut view = staging.stage(&self.buffer.buffer, start as u64..end as u64);
+      view.slice(..available - start).copy_from_slice(&bytes[start..available]);
+      if available < end { view.slice(available - start..).fill(0) }
+    }
+
+    reallocated
+  }
+}
+
+impl<T: AsBytes> Deref for GpuVec<T> {
+  type Target = [T];
+  fn deref(&self) -> &[T] { &self.data }
+}
+
@@ -0,0 +1,213 @@
+use std::path::Path;
+use image::{ImageFormat, ImageBuffer, RgbaImage, Rgba32FImage, DynamicImage};
+use wgpu::TextureFormat;
+use half::f16;
+use crate::*;
+use anyhow::{Result as Res, bail, Context};
+
+
+// helper
+
+fn unorm8(color: Color) -> [u8; 4] {
+    color.f32().map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+
+// texels to linear rgba, srgb if the data is srgb encoded
+pub fn texels_to_linear(format: TextureFormat, srgb: bool, data: &[u8]) -> Res<Vec<Color>> {
+    use TextureFormat::*;
+
+    let srgb = srgb || format.is_srgb();
+    let decode = |color: Color| if srgb { color.linear() } else { color };
+
+    Ok(match format {
+        Rgba8Unorm | Rgba8UnormSrgb => data.chunks_exact(4).map(|c| decode(Color::from_u8([c[0], c[1], c[2], c[3]]))).collect(),
+        Bgra8Unorm | Bgra8UnormSrgb => data.chunks_exact(4).map(|c| decode(Color::from_u8([c[2], c[1], c[0], c[3]]))).collect(),
+        R8Unorm => data.iter().map(|v| decode(Color::from_value_u8(*v))).collect(),
+
+        Rgb10a2Unorm => data.chunks_exact(4).map(|c| {
+            let v = u32::from_le_bytes([c[0], c[1], c[2], c[3]]);
+            let unorm10 = |shift: u32| (v >> shift & 0x3FF) as f32 / 1023.0;
+            decode(Color::new(unorm10(0), unorm10(10), unorm10(20), (v >> 30) as f32 / 3.0))
+        }).collect(),
+
+        Rgba16Float => data.chunks_exact(8).map(|c| {
+            let channel = |i: usize| f16::from_le_bytes([c[2*i], c[2*i+1]]).to_f32();
+            Color::new(channel(0), channel(1), channel(2), channel(3))
+        }).collect(),
+
+        Rgba32Float => data.chunks_exact(16).map(|c| Color::from_f32(pod_read_unaligned(c))).collect(),
+        R32Float => data.chunks_exact(4).map(|c| Color::from_value_f32(pod_read_unaligned(c))).collect(),
+
+        _ => bail!("texture format {format:?} can't be converted to an image"),
+    })
+}
+
+// linear rgba to texels, srgb if the data should be srgb encoded
+pub fn linear_to_texels(format: TextureFormat, srgb: bool, colors: &[Color]) -> Res<Vec<u8>> {
+    use TextureFormat::*;
+
+    let srgb = srgb || format.is_srgb();
+    let encode = |color: Color| if srgb { color.srgb() } else { color };
+
+    Ok(match format {
+        Rgba8Unorm | Rgba8UnormSrgb => colors.iter().flat_map(|c| unorm8(encode(*c))).collect(),
+        Bgra8Unorm | Bgra8UnormSrgb => colors.iter().flat_map(|c| { let [r, g, b, a] = unorm8(encode(*c)); [b, g, r, a] }).collect(),
+        R8Unorm => colors.iter().map(|c| unorm8(encode(*c))[0]).collect(),
+        Rgba16Float => colors.iter().flat_map(|c| c.f32()).flat_map(|v| f16::from_f32(v).to_le_bytes()).collect(),
+        Rgba32Float => colors.iter().flat_map(|c| c.f32()).flat_map(f32::to_le_bytes).collect(),
+        R32Float => colors.iter().flat_map(|c| c.r.to_le_bytes()).collect(),
+        _ => bail!("texture format {format:?} can't be converted from an image"),
+    })
+}
+
+// texels to srgb encoded rgba8
+pub fn texels_to_srgb8(format: TextureFormat, srgb: bool, data: &[u8]) -> Res<Vec<u8>> {
+    use TextureFormat::*;
+
+    let srgb = srgb || format.is_srgb();
+
+    Ok(match format {
+        Rgba8Unorm | Rgba8UnormSrgb if srgb => data.to_vec(),
+        Bgra8Unorm | Bgra8UnormSrgb if srgb => data.chunks_exact(4).flat_map(|c| [c[2], c[1], c[0], c[3]]).collect(),
+        _ => texels_to_linear(format, srgb, data)?.into_iter().flat_map(|color| unorm8(color.srgb())).collect(),
+    })
+}
+
+
+// save textures to image files, reading back the first mip level
+pub trait SaveImage: ReadPixels {
+
+    fn srgb_encoded(&self) -> bool; // if the texels are srgb encoded
+
+    fn to_rgba8_image(&self, gx: &impl WgxDeviceQueue) -> Res<RgbaImage> {
+        let texture = self.readback_texture();
+        let data = self.read_pixels(gx, TexRegion::all(), 0)?;
+        let data = texels_to_srgb8(texture.format(), self.srgb_encoded(), &data)?;
+        ImageBuffer::from_raw(texture.width(), texture.height(), data).context("image size mismatch")
+    }
+
+    fn to_rgba32f_image(&self, gx: &impl WgxDeviceQueue) -> Res<Rgba32FImage> {
+        let texture = self.readback_texture();
+        let data = self.read_pixels(gx, TexRegion::all(), 0)?;
+        let data = texels_to_linear(texture.format(), self.srgb_encoded(), &data)?;
+        ImageBuffer::from_raw(texture.width(), texture.height(), data.into_iter().flat_map(Color::f32).collect()).context("image size mismatch")
+    }
+
+    fn save_png(&self, gx: &impl WgxDeviceQueue, path: impl AsRef<Path>) -> Res<()> {
+        Ok(self.to_rgba8_image(gx)?.save_with_format(path, ImageFormat::Png)?)
+    }
+
+    // without alpha
+    fn save_ppm(&self, gx: &impl WgxDeviceQueue, path: impl AsRef<Path>) -> Res<()> {
+        Ok(DynamicImage::from(self.to_rgba8_image(gx)?).to_rgb8().save_with_format(path, ImageFormat::Pnm)?)
+    }
+
+    // linear
+    fn save_exr(&self, gx: &impl WgxDeviceQueue, path: impl AsRef<Path>) -> Res<()> {
+        Ok(self.to_rgba32f_image(gx)?.save_with_format(path, ImageFormat::OpenExr)?)
+    }
+}
+
+impl SaveImage for wgpu::Texture {
+    fn srgb_encoded(&self) -> bool { self.format().is_srgb() }
+}
+
+impl SaveImage for TextureLot {
+    fn srgb_encoded(&self) -> bool { self.descriptor.srgb() }
+}
+
+impl SaveImage for TextureTarget {
+    fn srgb_encoded(&self) -> bool { self.descriptor.srgb() }
+}
+
+
+// texture loading
+
+// 2x2 box filter
+fn downsample(colors: &[Color], [width, height]: [u32; 2]) -> (Vec<Color>, [u32; 2]) {
+    let size = [(width / 2).max(1), (height / 2).max(1)];
+    let at = |x: u32, y: u32| colors[(y.min(height - 1) * width + x.min(width - 1)) as usize];
+
+    let level = (0..size[1]).flat_map(|y| (0..size[0]).map(move |x| {
+        at(2*x, 2*y).add(at(2*x + 1, 2*y)).add(at(2*x, 2*y + 1)).add(at(2*x + 1, 2*y + 1)).mul(0.25)
+    })).collect();
+
+    (level, size)
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageOptions {
+    pub srgb: bool, // color data, float images are always linear
+    pub premultiply: bool,
+    pub mipmaps: bool, // generated on the cpu, filtered in linear space
+    pub usage: TexUse,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self { srgb: true, premultiply: false, mipmaps: false, usage: TexUse::TEXTURE_BINDING | TexUse::COPY_DST }
+    }
+}
+
+impl ImageOptions {
+
+    pub fn new() -> Self { Self::default() }
+
+    pub fn srgb(mut self, srgb: bool) -> Self { self.srgb = srgb; self }
+    pub fn premultiply(mut self, premultiply: bool) -> Self { self.premultiply = premultiply; self }
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self { self.mipmaps = mipmaps; self }
+    pub fn usage(mut self, usage: TexUse) -> Self { self.usage = usage; self }
+
+    pub fn format(&self, image: &DynamicImage) -> TextureFormat {
+        match image {
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => TextureFormat::Rgba16Float,
+            DynamicImage::ImageLuma8(_) if !self.srgb => TextureFormat::R8Unorm,
+            _ if self.srgb => TextureFormat::Rgba8UnormSrgb,
+            _ => TextureFormat::Rgba8Unorm,
+        }
+    }
+}
+
+
+impl TextureLot {
+
+    pub fn from_image(gx: &impl WgxDeviceQueue, image: &DynamicImage, options: ImageOptions) -> Res<Self> {
+
+        let format = options.format(image);
+        let mut size = [image.width(), image.height()];
+        let mut descriptor = TexDsc::new_2d([size[0], size[1], 1], 1, format, None, options.usage);
+
+        let mut colors: Vec<Color> = match format {
+            TextureFormat::Rgba16Float => image.to_rgba32f().pixels().map(|pixel| Color::from_f32(pixel.0)).collect(),
+            _ => texels_to_linear(TextureFormat::Rgba8Unorm, options.srgb, image.to_rgba8().as_raw())?,
+        };
+
+        if options.premultiply {
+            for color in &mut colors { *color = color.premul() }
+        }
+
+        let mut data = linear_to_texels(format, false, &colors)?;
+
+        if options.mipmaps {
+            descriptor.mip_level_count = descriptor.max_mip_level_count();
+            for _ in 1..descriptor.mip_level_count {
+                (colors, size) = downsample(&colors, size);
+                data.extend(linear_to_texels(format, false, &colors)?);
+            }
+        }
+
+        Ok(Self::new_with_data(gx, descriptor, data.as_slice()))
+    }
+
+    // format is guessed from the content
+    pub fn from_image_bytes(gx: &impl WgxDeviceQueue, bytes: &[u8], options: ImageOptions) -> Res<Self> {
+        Self::from_image(gx, &image::load_from_memory(bytes)?, options)
+    }
+
+    // format is guessed from the extension
+    pub fn from_path(gx: &impl WgxDeviceQueue, path: impl AsRef<Path>, options: ImageOptions) -> Res<Self> {
+        Self::from_image(gx, &image::open(path)?, options)
+    }
+}
@@ -29,6 +29,10 @@ mod macros;
 mod read_bytes;
 pub use read_bytes::*;
 
+mod wgsl_layout;
+pub use wgsl_layout::*;
+pub use wgx_macro::*;
+
 mod color;
 pub use color::*;
 
@@ -36,6 +40,15 @@ pub use color::*;
 mod wgx;
 pub use wgx::*;
 
+mod adapter_selector;
+pub use adapter_selector::*;
+
+mod device_request;
+pub use device_request::*;
+
+mod device_errors;
+pub use device_errors::*;
+
 mod pipeline_config;
 pub use pipeline_config::*;
 
@@ -48,6 +61,15 @@ pub use texture_extension::*;
 mod render_target;
 pub use render_target::*;
 
+mod texture_readback;
+pub use texture_readback::*;
+
+mod mipmap_generator;
+pub use mipmap_generator::*;
+
+mod texture_atlas;
+pub use texture_atlas::*;
+
 mod util_extension;
 pub use util_extension::*;
 
@@ -57,17 +79,50 @@ pub use staging_extension::*;
 mod buffer_helper;
 pub use buffer_helper::*;
 
+mod typed_buffer;
+pub use typed_buffer::*;
+
+mod gpu_vec;
+pub use gpu_vec::*;
+
+mod buffer_allocator;
+pub use buffer_allocator::*;
+
+mod buffer_readback;
+pub use buffer_readback::*;
+
+mod readback_belt;
+pub use readback_belt::*;
+
 
 // features
 
 #[cfg(feature = "math")]
 pub mod math;
 
+#[cfg(feature = "image")]
+mod image_export;
+
+#[cfg(feature = "image")]
+pub use {image, image_export::*};
+
+#[cfg(any(feature = "ktx2", feature = "dds"))]
+mod texture_file;
+
+#[cfg(any(feature = "ktx2", feature = "dds"))]
+pub use texture_file::*;
+
 
 // wgsl modules
 #[cfg(feature = "wgsl_modules")]
 pub use wgsl_modules;
 
+#[cfg(feature = "wgsl_modules_loader")]
+mod shader_reflection;
+
+#[cfg(feature = "wgsl_modules_loader")]
+pub use shader_reflection::*;
+
 
 // control flow helper
 
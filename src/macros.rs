@@ -158,6 +158,12 @@ macro_rules! bind {
             resource: $crate::wgpu::BindingResource::Buffer($crate::bind_buffer!($buffer, $offset, $size)),
         }
     };
+    ($loc:expr, BufferBinding, $binding:expr) => {
+        $crate::wgpu::BindGroupEntry {
+            binding: $loc,
+            resource: $crate::wgpu::BindingResource::Buffer($binding),
+        }
+    };
     ($loc:expr, $ty:ident, $value:expr) => {
         $crate::wgpu::BindGroupEntry {
             binding: $loc,
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use wgpu::{TextureFormat, TextureDimension, TextureViewDimension, TextureViewDescriptor, TextureFormatFeatures, TextureFormatFeatureFlags};
+use crate::*;
+use anyhow::{Result as Res, bail};
+
+
+// adapter specific format features are only usable with the TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES feature
+fn format_features(device: &wgpu::Device, adapter: &wgpu::Adapter, format: TextureFormat) -> TextureFormatFeatures {
+    if device.features().contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+        adapter.get_texture_format_features(format)
+    } else {
+        format.guaranteed_format_features(device.features())
+    }
+}
+
+
+const SHADER: &str = r"
+struct VertexOut {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u)); // fullscreen triangle
+    return VertexOut(vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0), uv);
+}
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4f {
+    return textureSample(source, source_sampler, in.uv);
+}
+";
+
+
+// renders every mip level from the previous one with linear filtering,
+// srgb view formats are decoded on sampling and encoded on writing, so filtering happens in linear space
+#[derive(Debug)]
+pub struct MipmapGenerator {
+    shader: wgpu::ShaderModule,
+    layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    pipelines: HashMap<TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl MipmapGenerator {
+
+    pub fn new(gx: &impl WgxDevice) -> Self {
+
+        let layout = gx.layout(&[
+            binding!(0, Stage::FRAGMENT, Texture, D2, Float),
+            binding!(1, Stage::FRAGMENT, Sampler, Filtering),
+        ]);
+
+        Self {
+            shader: gx.load_wgsl(SHADER),
+            pipeline_layout: gx.pipeline_layout(0, &[Some(&layout)]),
+            layout,
+            sampler: gx.sampler(&std_sampler_descriptor()),
+            pipelines: HashMap::new(),
+        }
+    }
+
+    // cached per format
+    pub fn pipeline(&mut self, gx: &impl WgxDevice, format: TextureFormat) -> &wgpu::RenderPipeline {
+        let Self { shader, pipeline_layout, pipelines, .. } = self;
+
+        pipelines.entry(format).or_insert_with(|| {
+            RenderPipelineConfig::new(&[], shader, "vs_main", Primitive::default())
+                .layout(pipeline_layout.clone())
+                .fragment(shader, "fs_main")
+                .target::<1>(format.target())
+                .pipeline(gx)
+        })
+    }
+
+    // all layers of a 2d texture, the texture needs TEXTURE_BINDING and RENDER_ATTACHMENT usage
+    // (the gles backend can only sample the first layer)
+    pub fn generate_texture(
+        &mut self, gx: &impl WgxDevice, adapter: &wgpu::Adapter, encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture, view_format: Option<TextureFormat>,
+    ) -> Res<()> {
+
+        let format = view_format.unwrap_or(texture.format());
+
+        if texture.dimension() != TextureDimension::D2 {
+            bail!("mipmaps can only be generated for 2d textures and arrays, not {:?}", texture.dimension());
+        }
+        if !texture.usage().contains(TexUse::TEXTURE_BINDING | TexUse::RENDER_ATTACHMENT) {
+            bail!("texture needs TEXTURE_BINDING and RENDER_ATTACHMENT usage to generate mipmaps");
+        }
+        if texture.sample_count() > 1 {
+            bail!("mipmaps can't be generated for multisampled textures");
+        }
+
+        let format_features = format_features(gx.device(), adapter, format);
+
+        if
+            !format_features.flags.contains(TextureFormatFeatureFlags::FILTERABLE) ||
+            !format_features.allowed_usages.contains(TexUse::RENDER_ATTACHMENT)
+        {
+            bail!("texture format {format:?} is not filterable and renderable");
+        }
+
+        let pipeline = self.pipeline(gx, format).clone();
+
+        let view = |mip, layer| texture.create_view(&TextureViewDescriptor {
+            format: Some(format),
+            dimension: Some(TextureViewDimension::D2),
+            base_mip_level: mip, mip_level_count: Some(1),
+            base_array_layer: layer, array_layer_count: Some(1),
+            ..TextureViewDescriptor::default()
+        });
+
+        for layer in 0..texture.depth_or_array_layers() {
+
+            let mut source = view(0, layer);
+
+            for mip in 1..texture.mip_level_count() {
+
+                let target = view(mip, layer);
+
+                let binding = gx.bind(&self.layout, &[
+                    bind!(0, TextureView, &source),
+                    bind!(1, Sampler, &self.sampler),
+                ]);
+
+                let attachment = ColorAttachment { view: &target, format, msaa: None, clear: None };
+                let mut rpass = encoder.render_pass(([Some(attachment.into())], None));
+
+                rpass.set_pipeline(&pipeline);
+                rpass.set_bind_group(0, &binding, &[]);
+                rpass.draw(0..3, 0..1);
+
+                drop(rpass);
+                source = target;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn generate(&mut self, gx: &impl WgxDevice, adapter: &wgpu::Adapter, encoder: &mut wgpu::CommandEncoder, texture: &TextureLot) -> Res<()> {
+        self.generate_texture(gx, adapter, encoder, &texture.texture, Some(texture.descriptor.view_format))
+    }
+}
+
+
+impl TextureLot {
+
+    // creates a generator each time, keep a MipmapGenerator around for repeated use
+    pub fn generate_mipmaps(&self, gx: &impl WgxDevice, adapter: &wgpu::Adapter, encoder: &mut wgpu::CommandEncoder) -> Res<()> {
+        MipmapGenerator::new(gx).generate(gx, adapter, encoder, self)
+    }
+}
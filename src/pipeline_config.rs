@@ -128,6 +128,11 @@ impl<'a, const N: usize> RenderPipelineConfig<'a, N> {
     }
 
     pub fn target<const C: usize>(self, target: Option<wgpu::ColorTargetState>) -> RenderPipelineConfig<'a, C> {
+        self.targets(&[target])
+    }
+
+    pub fn targets<const C: usize>(self, targets: &[Option<wgpu::ColorTargetState>]) -> RenderPipelineConfig<'a, C> {
+        assert!(N + targets.len() <= C, "too many targets"); // remaining targets are None
         RenderPipelineConfig {
             label: self.label,
             cache: self.cache,
@@ -144,7 +149,7 @@ impl<'a, const N: usize> RenderPipelineConfig<'a, N> {
                 targets: {
                     let mut acc_targets = [const {None}; C];
                     acc_targets[0..N].clone_from_slice(&config.targets);
-                    acc_targets[N] = target;
+                    acc_targets[N..(N + targets.len())].clone_from_slice(targets);
                     acc_targets
                 },
             }),
@@ -152,10 +157,11 @@ impl<'a, const N: usize> RenderPipelineConfig<'a, N> {
     }
 
     pub fn render_target<const C: usize>(self, render_target: &impl RenderTarget, blend: Option<Blend>, write_mask: wgpu::ColorWrites) -> RenderPipelineConfig<'a, C> {
-        let config = self
-            .target((render_target.format(), blend, write_mask).target())
-            .msaa(render_target.msaa())
+        let targets: Vec<_> = render_target.color_formats().into_iter()
+            .map(|format| format.and_then(|format| (format, blend, write_mask).target()))
+            .collect()
         ;
+        let config = self.targets(&targets).msaa(render_target.msaa());
         if let Some(format) = render_target.depth_testing() {
             config.depth_testing(format)
         }
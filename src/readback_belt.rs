@@ -0,0 +1,234 @@
+use std::{fmt, ops::{Range, RangeBounds}, task::{Context, Poll, Waker}};
+use wgpu::{Buffer, BufferAddress, CommandEncoder, COPY_BUFFER_ALIGNMENT, COPY_BYTES_PER_ROW_ALIGNMENT};
+use crate::{*};
+use anyhow::{Result as Res, anyhow};
+
+
+struct BeltRead {
+  range: Range<BufferAddress>,
+  layout: Option<ReadbackLayout>, // texture reads are unpadded on delivery
+  callback: ReadbackCallback,
+}
+
+struct Chunk {
+  buffer: Buffer,
+  offset: BufferAddress,
+  reads: Vec<BeltRead>,
+}
+
+impl Chunk {
+  fn deliver(&mut self) {
+    let mapped = self.buffer.slice(..).get_mapped_range();
+    for read in self.reads.drain(..) {
+      let bytes = &mapped[read.range.start as usize..read.range.end as usize];
+      (read.callback)(Ok(match read.layout {
+        Some(layout) => layout.unpad(bytes),
+        None => bytes.to_vec(),
+      }));
+    }
+  }
+}
+
+
+// download counterpart of wgpu::util::StagingBelt, copies are recorded into chunks of
+// MAP_READ buffers that are reused once their contents were delivered:
+// record copies, call finish before and recall after submitting the encoder,
+// recall delivers finished readbacks without blocking, the device has to be polled meanwhile
+
+pub struct ReadbackBelt {
+  device: wgpu::Device,
+  chunk_size: BufferAddress,
+  active: Vec<Chunk>,
+  closed: Vec<Chunk>,
+  mapping: Vec<(Chunk, MapFuture)>,
+  free: Vec<Chunk>,
+}
+
+impl fmt::Debug for ReadbackBelt {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("ReadbackBelt")
+      .field("chunk_size", &self.chunk_size)
+      .field("active", &self.active.len())
+      .field("closed", &self.closed.len())
+      .field("mapping", &self.mapping.len())
+      .field("free", &self.free.len())
+      .finish()
+  }
+}
+
+impl ReadbackBelt {
+
+  pub fn new(gx: &impl WgxDevice, chunk_size: BufferAddress) -> Self {
+    Self {
+      device: gx.device().clone(), chunk_size,
+      active: Vec::new(), closed: Vec::new(), mapping: Vec::new(), free: Vec::new(),
+    }
+  }
+
+  // readbacks recorded or waiting for their mapping
+  pub fn pending(&self) -> usize {
+    self.active.iter().chain(&self.closed).chain(self.mapping.iter().map(|(chunk, _)| chunk))
+      .map(|chunk| chunk.reads.len()).sum()
+  }
+
+  // returns the chunk index in active and the offset of the allocated range
+  fn allocate(&mut self, size: BufferAddress, alignment: BufferAddress) -> (usize, BufferAddress) {
+
+    let fits = |chunk: &Chunk| chunk.offset.next_multiple_of(alignment) + size <= chunk.buffer.size();
+
+    let index = match self.active.iter().position(fits) {
+      Some(index) => index,
+      None => {
+        let chunk = match self.free.iter().position(fits) {
+          Some(index) => self.free.swap_remove(index),
+          None => Chunk {
+            buffer: self.device.buffer(BufUse::MAP_READ | BufUse::COPY_DST, self.chunk_size.max(size), false),
+            offset: 0, reads: Vec::new(),
+          },
+        };
+        self.active.push(chunk);
+        self.active.len() - 1
+      },
+    };
+
+    let chunk = &mut self.active[index];
+    let offset = chunk.offset.next_multiple_of(alignment);
+    chunk.offset = offset + size;
+
+    (index, offset)
+  }
+
+  fn push_read(
+    &mut self, index: usize, range: Range<BufferAddress>, layout: Option<ReadbackLayout>,
+    callback: impl FnOnce(Res<Vec<u8>>) + Send + 'static,
+  ) {
+    self.active[index].reads.push(BeltRead { range, layout, callback: Box::new(callback) });
+  }
+
+  // close the active chunks, call before submitting the encoder the copies were recorded to
+  pub fn finish(&mut self) {
+    self.closed.append(&mut self.active);
+  }
+
+  // request mappings of closed chunks and deliver finished readbacks, call after submitting,
+  // returns the number of readbacks still pending
+  pub fn recall(&mut self) -> usize {
+
+    for chunk in self.closed.drain(..) {
+      let mapped = Box::pin(map_async(&chunk.buffer.slice(..), MapMode::Read));
+      self.mapping.push((chunk, mapped));
+    }
+
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut i = 0;
+
+    while i < self.mapping.len() {
+      if let Poll::Ready(result) = self.mapping[i].1.as_mut().poll(&mut cx) {
+        let (mut chunk, _) = self.mapping.remove(i);
+        match result {
+          Ok(()) => {
+            chunk.deliver();
+            chunk.buffer.unmap();
+            chunk.offset = 0;
+            self.free.push(chunk);
+          },
+          // the chunk is dropped
+          Err(err) => for read in chunk.reads {
+            (read.callback)(Err(anyhow!("mapping readback chunk failed: {err}")));
+          },
+        }
+      }
+      else { i += 1 }
+    }
+
+    self.pending()
+  }
+
+  // polls the device without blocking and recalls
+  pub fn poll(&mut self, gx: &impl WgxDevice) -> Res<usize> {
+    gx.device().poll(wgpu::PollType::Poll)?;
+    Ok(self.recall())
+  }
+
+  // drops unused chunks
+  pub fn trim(&mut self) {
+    self.free.clear();
+  }
+}
+
+
+pub trait ReadbackBeltExtension {
+
+  // the source needs COPY_SRC usage, offset and size have to be aligned to COPY_BUFFER_ALIGNMENT
+  fn copy_buffer_to_belt(
+    &mut self, belt: &mut ReadbackBelt, source: &Buffer, range: impl RangeBounds<BufferAddress>,
+    callback: impl FnOnce(Res<Vec<u8>>) + Send + 'static,
+  ) -> Res<()>;
+
+  // delivers tightly packed texels, see ReadPixels
+  fn copy_texture_to_belt(
+    &mut self, belt: &mut ReadbackBelt, texture: &impl ReadPixels, region: TexRegion, mip: u32,
+    callback: impl FnOnce(Res<Vec<u8>>) + Send + 'static,
+  ) -> Res<()>;
+}
+
+impl ReadbackBeltExtension for CommandEncoder {
+
+  fn copy_buffer_to_belt(
+    &mut self, belt: &mut ReadbackBelt, source: &Buffer, range: impl RangeBounds<BufferAddress>,
+    callback: impl FnOnce(Res<Vec<u8>>) + Send + 'static,
+  ) -> Res<()> {
+
+    let range = range.map_into(0..source.size())?;
+    check_copy_range(source, &range)?;
+
+    let size = range.end - range.start;
+    let (index, offset) = belt.allocate(size, COPY_BUFFER_ALIGNMENT);
+
+    self.copy_buffer_to_buffer(source, range.start, &belt.active[index].buffer, offset, size);
+    belt.push_read(index, offset..(offset + size), None, callback);
+
+    Ok(())
+  }
+
+  fn copy_texture_to_belt(
+    &mut self, belt: &mut ReadbackBelt, texture: &impl ReadPixels, region: TexRegion, mip: u32,
+    callback: impl FnOnce(Res<Vec<u8>>) + Send + 'static,
+  ) -> Res<()> {
+
+    let size = texture.readback_layout(region, mip)?.buffer_size();
+
+    // satisfies the offset alignment of every texel block size
+    let (index, offset) = belt.allocate(size, COPY_BYTES_PER_ROW_ALIGNMENT as u64);
+
+    let layout = texture.copy_to_buffer(self, region, mip, &belt.active[index].buffer, offset)?;
+    belt.push_read(index, offset..(offset + size), Some(layout), callback);
+
+    Ok(())
+  }
+}
+
+
+impl StagingEncoder {
+
+  // submits with staged writes and readbacks, returns the number of readbacks still pending
+  pub fn submit_with_readback(&mut self, gx: &impl WgxDeviceQueue, readback: &mut ReadbackBelt) -> usize {
+    readback.finish();
+    self.submit(gx);
+    readback.recall()
+  }
+
+  pub fn copy_buffer_to_belt(
+    &mut self, belt: &mut ReadbackBelt, source: &Buffer, range: impl RangeBounds<BufferAddress>,
+    callback: impl FnOnce(Res<Vec<u8>>) + Send + 'static,
+  ) -> Res<()> {
+    self.encoder.copy_buffer_to_belt(belt, source, range, callback)
+  }
+
+  pub fn copy_texture_to_belt(
+    &mut self, belt: &mut ReadbackBelt, texture: &impl ReadPixels, region: TexRegion, mip: u32,
+    callback: impl FnOnce(Res<Vec<u8>>) + Send + 'static,
+  ) -> Res<()> {
+    self.encoder.copy_texture_to_belt(belt, texture, region, mip, callback)
+  }
+}
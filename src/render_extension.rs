@@ -71,6 +71,33 @@ impl<'a> From<DepthAttachment<'a>> for wgpu::RenderPassDepthStencilAttachment<'a
 }
 
 
+// color space of a render target
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    #[default] Srgb,
+    Srgb10, // 10 bit per channel
+    ExtendedLinear, // float formats, linear with values beyond 1.0
+}
+
+impl ColorSpace {
+    pub fn from_format(format: TextureFormat) -> Self {
+        match format {
+            TextureFormat::Rgba16Float | TextureFormat::Rgba32Float => Self::ExtendedLinear,
+            TextureFormat::Rgb10a2Unorm => Self::Srgb10,
+            _ => Self::Srgb,
+        }
+    }
+    pub fn is_hdr(self) -> bool { self == Self::ExtendedLinear }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TonemapHint {
+    Sdr, // tonemap into [0, 1]
+    Hdr, // output scene values, above 1.0 is brighter than sdr white
+}
+
+
 // render target
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -93,6 +120,15 @@ pub trait RenderTarget {
         TargetDsc { size: self.size(), msaa: self.msaa(), depth_testing: self.depth_testing(), format: self.format() }
     }
 
+    // formats of all color attachments, a single one by default
+    fn color_formats(&self) -> Vec<Option<TextureFormat>> { vec![Some(self.format())] }
+
+    fn color_space(&self) -> ColorSpace { ColorSpace::from_format(self.format()) }
+
+    fn tonemap_hint(&self) -> TonemapHint {
+        if self.color_space().is_hdr() { TonemapHint::Hdr } else { TonemapHint::Sdr }
+    }
+
     fn bytes_per_row(&self) -> Option<u32> {
         self.format().block_copy_size(None).map(|bytes| bytes * self.size()[0])
     }
@@ -100,8 +136,9 @@ pub trait RenderTarget {
     fn render_bundle_encoder<'a>(&self, gx: &'a impl WgxDevice, config_fn: impl FnOnce(&mut wgpu::RenderBundleEncoderDescriptor))
         -> wgpu::RenderBundleEncoder<'a>
     {
+        let formats = self.color_formats();
         gx.render_bundle_encoder(
-            render_bundle_encoder_descriptor(self.msaa(), self.depth_testing(), &[Some(self.format())]),
+            render_bundle_encoder_descriptor(self.msaa(), self.depth_testing(), &formats),
             config_fn,
         )
     }
@@ -1,7 +1,7 @@
 
 use wgpu::{*, PresentMode as Prs};
 use crate::*;
-use anyhow::{Result as Res, bail};
+use anyhow::{Result as Res};
 
 
 #[derive(Debug, Clone)]
@@ -34,9 +34,77 @@ impl TextureLot {
     ) -> Self {
         Self::new_with_data(gx, TexDsc::new_2d(size, sample_count, format, view_format, usage), data)
     }
+    pub fn new_cube_with_faces<T: ReadBytes>(
+        gx:&impl WgxDeviceQueue, size:u32,
+        format:TextureFormat, view_format:Option<TextureFormat>, usage:TexUse, faces: [T; 6],
+    ) -> Self {
+        let lot = Self::new(gx, TexDsc::new_cube(size, format, view_format, usage | TexUse::COPY_DST));
+        lot.write_layers(gx, faces);
+        lot
+    }
     pub fn update_view(&mut self) {
         self.view = self.texture.create_view(&self.descriptor.default_view());
     }
+
+    pub fn mip_view(&self, mip: u32) -> wgpu::TextureView {
+        self.texture.create_view(&self.descriptor.mip_view(mip))
+    }
+    // Err for 1d and 3d textures
+    pub fn layer_view(&self, layer: u32) -> Res<wgpu::TextureView> {
+        Ok(self.texture.create_view(&self.descriptor.layer_view(layer)?))
+    }
+    // for cube arrays use layer_view(cube * 6 + face.layer())
+    pub fn face_view(&self, face: CubeFace) -> Res<wgpu::TextureView> {
+        self.layer_view(face.layer())
+    }
+
+    pub fn view_target(&self, mip: u32, layer: u32) -> Res<ViewTarget> {
+        let [width, height, _] = self.descriptor.mip_size(mip);
+        Ok(ViewTarget {
+            view: self.texture.create_view(&self.descriptor.mip_layer_view(mip, layer)?),
+            size: [width, height], format: self.descriptor.view_format, mip, layer,
+        })
+    }
+    // mip chain of a layer
+    pub fn mip_targets(&self, layer: u32) -> Res<Vec<ViewTarget>> {
+        (0..self.descriptor.mip_level_count).map(|mip| self.view_target(mip, layer)).collect()
+    }
+    // e.g. cube faces or shadow cascades
+    pub fn layer_targets(&self, mip: u32) -> Res<Vec<ViewTarget>> {
+        (0..self.descriptor.array_layer_count()).map(|layer| self.view_target(mip, layer)).collect()
+    }
+
+    // tightly packed data of a whole layer of the first mip level
+    pub fn write_layer<T: ReadBytes>(&self, gx:&impl WgxQueue, layer: u32, data: T) {
+        let [width, height, _] = self.descriptor.size;
+        let format = self.descriptor.format;
+        let (block_width, block_height) = format.block_dimensions();
+        let bytes_per_row = format.block_copy_size(None).map(|size| width.div_ceil(block_width) * size);
+
+        gx.write_texture(
+            (&self.texture, 0, [0, 0, layer]),
+            (data, (0, bytes_per_row, Some(height.div_ceil(block_height)))),
+            [width, height, 1],
+        );
+    }
+    pub fn write_layers<T: ReadBytes>(&self, gx:&impl WgxQueue, layers: impl IntoIterator<Item=T>) {
+        for (layer, data) in layers.into_iter().enumerate() {
+            self.write_layer(gx, layer as u32, data);
+        }
+    }
+}
+
+
+// recreate textures with a new size, keeping their configuration
+pub trait Resizable {
+    fn resize(&mut self, gx: &impl WgxDevice, size: impl Into<[u32; 2]>);
+}
+
+impl Resizable for TextureLot {
+    fn resize(&mut self, gx: &impl WgxDevice, size: impl Into<[u32; 2]>) {
+        self.descriptor.set_size_2d(size.into());
+        *self = Self::new(gx, self.descriptor);
+    }
 }
 
 impl RenderTarget for TextureLot {
@@ -55,18 +123,112 @@ impl RenderAttachable for TextureLot {
 
 
 
+// single mip level and layer of a texture
+#[derive(Debug, Clone)]
+pub struct ViewTarget {
+    pub view: wgpu::TextureView,
+    pub size: [u32; 2],
+    pub format: TextureFormat,
+    pub mip: u32,
+    pub layer: u32,
+}
+
+impl RenderTarget for ViewTarget {
+    fn size(&self) -> [u32; 2] { self.size }
+    fn msaa(&self) -> u32 { 1 }
+    fn depth_testing(&self) -> Option<TextureFormat> { self.format.is_depth_stencil_format().then_some(self.format) }
+    fn format(&self) -> TextureFormat { self.format }
+
+    fn color_formats(&self) -> Vec<Option<TextureFormat>> {
+        if self.format.is_depth_stencil_format() { Vec::new() } else { vec![Some(self.format)] }
+    }
+}
+
+impl RenderAttachable for ViewTarget {
+    fn color_views(&self) -> (&wgpu::TextureView, wgpu::TextureFormat, Option<&wgpu::TextureView>) { (&self.view, self.format, None) }
+    fn depth_view(&self) -> Option<(&wgpu::TextureView, wgpu::TextureFormat)> {
+        self.format.is_depth_stencil_format().then_some((&self.view, self.format))
+    }
+
+    // depth formats are attached as depth only
+    fn attachments(&self, clear_color: Option<crate::Color>, clear_depth: Option<f32>, clear_stencil: Option<u32>) -> RenderAttachments<'_, 1> {
+        if self.format.is_depth_stencil_format() {
+            ([None], self.depth_attachment(clear_depth, clear_stencil).map(|a| a.into()))
+        } else {
+            ([Some(self.color_attachment(clear_color).into())], None)
+        }
+    }
+}
+
+impl ViewTarget {
+    // depth only pass for depth formats, e.g. shadow maps
+    pub fn depth_attachments(&self, clear_depth: Option<f32>, clear_stencil: Option<u32>) -> RenderAttachments<'_, 0> {
+        ([], Some(DepthAttachment { view: &self.view, format: self.format, clear_depth, clear_stencil }.into()))
+    }
+}
+
+
+
 type Surface = wgpu::Surface<'static>;
 
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameStatus {
+    Ready,
+    Suboptimal, // frame acquired, but the surface should be reconfigured
+    Reconfigured, // frame acquired after reconfiguring the surface
+    Occluded, // skipped
+    Timeout, // skipped
+    Outdated,
+    Lost,
+    Validation,
+}
+
+impl FrameStatus {
+    pub fn has_frame(self) -> bool { matches!(self, Self::Ready | Self::Suboptimal | Self::Reconfigured) }
+    pub fn is_skipped(self) -> bool { matches!(self, Self::Occluded | Self::Timeout) }
+}
+
+impl std::fmt::Display for FrameStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SurfaceTexture: {self:?}")
+    }
+}
+
+impl std::error::Error for FrameStatus {}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SurfaceRecovery {
+    pub reconfigure_suboptimal: bool, // opt-in, at most once until the next update
+    pub max_retries: u32, // reconfigure and retry on Outdated, Lost and Suboptimal
+}
+
+impl SurfaceRecovery {
+    pub const NONE: Self = Self { reconfigure_suboptimal: false, max_retries: 0 };
+}
+
+impl Default for SurfaceRecovery {
+    fn default() -> Self { Self { reconfigure_suboptimal: false, max_retries: 1 } }
+}
+
+
 #[derive(Debug)]
 pub struct SurfaceTarget {
     pub config: SurfaceConfiguration,
     pub surface: Surface,
+    pub device: wgpu::Device,
     pub frame: Option<SurfaceTexture>,
     pub is_suboptimal: bool,
+    pub recovery: SurfaceRecovery,
+    pub options: SurfaceOptions,
+    pub color_space: ColorSpace,
     pub view_format: TextureFormat,
+    pub srgb: bool, // requested srgb view format, kept when the format is selected again
     pub msaa: u32,
     pub msaa_opt: Option<TextureLot>,
     pub depth_opt: Option<TextureLot>,
+    suboptimal_reconfigured: bool,
 }
 
 impl RenderTarget for SurfaceTarget {
@@ -74,6 +236,7 @@ impl RenderTarget for SurfaceTarget {
     fn msaa(&self) -> u32 { self.msaa }
     fn depth_testing(&self) -> Option<TextureFormat> { self.depth_opt.as_ref().map(|d| d.format()) }
     fn format(&self) -> TextureFormat { self.view_format }
+    fn color_space(&self) -> ColorSpace { self.color_space }
 }
 
 impl Drop for SurfaceTarget {
@@ -95,6 +258,7 @@ impl RenderTarget for SurfaceFrame<'_> {
     fn msaa(&self) -> u32 { self.target.msaa() }
     fn depth_testing(&self) -> Option<TextureFormat> { self.target.depth_testing() }
     fn format(&self) -> TextureFormat { self.target.format() }
+    fn color_space(&self) -> ColorSpace { self.target.color_space() }
 }
 
 impl RenderAttachable for SurfaceFrame<'_> {
@@ -107,12 +271,67 @@ impl RenderAttachable for SurfaceFrame<'_> {
 }
 
 
-pub fn configure_surface_defaults(
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurfaceOptions {
+    pub present_modes: Vec<PresentMode>, // in order of preference, falls back to Fifo
+    pub alpha_modes: Vec<CompositeAlphaMode>, // in order of preference, falls back to the first supported
+    pub frame_latency: u32,
+    pub hdr: bool, // prefer extended range formats
+}
+
+impl Default for SurfaceOptions {
+    fn default() -> Self {
+        Self { present_modes: vec![Prs::Fifo], alpha_modes: vec![CompositeAlphaMode::Auto], frame_latency: 0, hdr: false }
+    }
+}
+
+// extended range surface formats in order of preference
+pub const HDR_SURFACE_FORMATS: [TextureFormat; 2] = [TextureFormat::Rgba16Float, TextureFormat::Rgb10a2Unorm];
+
+impl SurfaceOptions {
+
+    pub fn present_modes(mut self, modes: &[PresentMode]) -> Self { self.present_modes = modes.to_vec(); self }
+    pub fn alpha_modes(mut self, modes: &[CompositeAlphaMode]) -> Self { self.alpha_modes = modes.to_vec(); self }
+    pub fn frame_latency(mut self, latency: u32) -> Self { self.frame_latency = latency; self }
+    pub fn hdr(mut self, hdr: bool) -> Self { self.hdr = hdr; self }
+
+    pub fn format(&self, capabilites: &SurfaceCapabilities, srgb: bool) -> Option<TextureFormat> {
+        let hdr_format = self.hdr.then(||
+            HDR_SURFACE_FORMATS.into_iter().find(|fmt| capabilites.formats.contains(fmt))
+        ).flatten();
+        hdr_format.or_else(|| capabilites.formats.iter().copied().find(|fmt| fmt.is_srgb() == srgb))
+    }
+
+    pub fn present_mode(&self, capabilites: &SurfaceCapabilities) -> PresentMode {
+        self.present_modes.iter().copied().find(|mode|
+            matches!(mode, Prs::AutoVsync | Prs::AutoNoVsync) || capabilites.present_modes.contains(mode)
+        ).unwrap_or(Prs::Fifo)
+    }
+
+    pub fn alpha_mode(&self, capabilites: &SurfaceCapabilities) -> CompositeAlphaMode {
+        self.alpha_modes.iter().copied().find(|mode| capabilites.alpha_modes.contains(mode)).unwrap_or(capabilites.alpha_modes.first().copied().unwrap_or(CompositeAlphaMode::Auto))
+    }
+
+    pub fn apply(&self, config: &mut SurfaceConfiguration, capabilites: &SurfaceCapabilities) {
+
+        config.present_mode = self.present_mode(capabilites);
+
+        if !self.present_modes.contains(&config.present_mode) {
+            log::warn!("present modes {:?} not supported, using {:?}", self.present_modes, config.present_mode);
+        }
+
+        config.alpha_mode = self.alpha_mode(capabilites);
+        config.desired_maximum_frame_latency = self.frame_latency;
+    }
+}
+
+
+pub fn configure_surface(
     config: &mut SurfaceConfiguration, capabilites: &SurfaceCapabilities,
-    downlevel_flags: &DownlevelFlags, srgb: bool,
+    downlevel_flags: &DownlevelFlags, srgb: bool, options: &SurfaceOptions,
 ) {
     // format
-    if let Some(format) = capabilites.formats.iter().find(|fmt| fmt.is_srgb() == srgb) { config.format = *format; }
+    if let Some(format) = options.format(capabilites, srgb) { config.format = format; }
 
     let other_format = if config.format.is_srgb() {
         config.format.remove_srgb_suffix()
@@ -120,31 +339,44 @@ pub fn configure_surface_defaults(
         config.format.add_srgb_suffix()
     };
 
-    if downlevel_flags.contains(DownlevelFlags::SURFACE_VIEW_FORMATS) && !config.view_formats.contains(&other_format) {
+    if
+        other_format != config.format &&
+        downlevel_flags.contains(DownlevelFlags::SURFACE_VIEW_FORMATS) && !config.view_formats.contains(&other_format)
+    {
         config.view_formats.push(other_format);
     }
 
-    // present mode
-    config.present_mode = Prs::Fifo;
-
-    // alpha_modes
-    if capabilites.alpha_modes.contains(&CompositeAlphaMode::Auto) { config.alpha_mode = CompositeAlphaMode::Auto; }
+    // present mode, alpha mode, frame latency
+    options.apply(config, capabilites);
+}
 
-    // frame latency
-    config.desired_maximum_frame_latency = 0;
+pub fn configure_surface_defaults(
+    config: &mut SurfaceConfiguration, capabilites: &SurfaceCapabilities,
+    downlevel_flags: &DownlevelFlags, srgb: bool,
+) {
+    configure_surface(config, capabilites, downlevel_flags, srgb, &SurfaceOptions::default())
 }
 
 
 impl SurfaceTarget {
 
     pub fn new_with_default_config(gx:&Wgx, surface:Surface, size:impl Into<[u32; 2]>, srgb: bool, msaa:u32, depth_testing:Option<TextureFormat>) -> Self
+    {
+        Self::new_with_options(gx, surface, size, srgb, msaa, depth_testing, SurfaceOptions::default())
+    }
+
+
+    pub fn new_with_options(
+        gx:&Wgx, surface:Surface, size:impl Into<[u32; 2]>, srgb: bool, msaa:u32, depth_testing:Option<TextureFormat>,
+        options: SurfaceOptions,
+    ) -> Self
     {
         let [width, height] = size.into();
         let mut config = surface.get_default_config(&gx.adapter, width, height).unwrap();
 
-        configure_surface_defaults(
+        configure_surface(
             &mut config, &surface.get_capabilities(&gx.adapter),
-            &gx.adapter.get_downlevel_capabilities().flags, srgb,
+            &gx.adapter.get_downlevel_capabilities().flags, srgb, &options,
         );
 
         let format = config.format;
@@ -154,7 +386,10 @@ impl SurfaceTarget {
             assert!(config.view_formats.contains(&view_format), "view_formats may not be supported");
         }
 
-        Self::new(gx, surface, config, view_format, msaa, depth_testing)
+        let mut target = Self::new(gx, surface, config, view_format, msaa, depth_testing);
+        target.options = options;
+        target.srgb = srgb;
+        target
     }
 
 
@@ -162,8 +397,10 @@ impl SurfaceTarget {
         -> Self
     {
         let mut target = Self {
-            config, surface, frame: None, is_suboptimal: false,
-            view_format, msaa, msaa_opt: None, depth_opt: None,
+            config, surface, device: gx.device().clone(), frame: None, is_suboptimal: false,
+            recovery: SurfaceRecovery::default(), options: SurfaceOptions::default(),
+            color_space: ColorSpace::from_format(view_format), view_format, srgb: view_format.is_srgb(),
+            msaa, msaa_opt: None, depth_opt: None, suboptimal_reconfigured: false,
         };
         target.configure(gx, depth_testing);
         target
@@ -191,25 +428,125 @@ impl SurfaceTarget {
         let [width, height] = size.into();
         self.config.width = width;
         self.config.height = height;
+        self.suboptimal_reconfigured = false;
         self.configure(gx, self.depth_testing());
     }
 
 
-    pub fn request_frame(&mut self) -> Res<()> {
+    // switch present mode, alpha mode, frame latency or hdr at runtime
+    pub fn set_options(&mut self, adapter: &wgpu::Adapter, options: SurfaceOptions) {
+        let hdr_changed = options.hdr != self.options.hdr;
+        self.options = options;
 
-        use wgpu::CurrentSurfaceTexture::*;
+        if hdr_changed {
+            self.select_format(adapter);
+        } else {
+            self.options.apply(&mut self.config, &self.surface.get_capabilities(adapter));
+        }
+
+        self.reconfigure();
+    }
+
+    pub fn set_srgb(&mut self, adapter: &wgpu::Adapter, srgb: bool) {
+        self.srgb = srgb;
+        self.select_format(adapter);
+        self.reconfigure();
+    }
+
+    // selects surface and view format for the current options, doesn't reconfigure
+    fn select_format(&mut self, adapter: &wgpu::Adapter) {
+
+        let old_format = self.config.format;
+        self.config.view_formats.retain(|format| format.remove_srgb_suffix() != old_format.remove_srgb_suffix());
+
+        configure_surface(
+            &mut self.config, &self.surface.get_capabilities(adapter),
+            &adapter.get_downlevel_capabilities().flags, self.srgb, &self.options,
+        );
+
+        let format = self.config.format;
+        let view_format = if self.srgb { format.add_srgb_suffix() } else { format.remove_srgb_suffix() };
 
-        match self.surface.get_current_texture() {
-            Success(texture) => { self.frame = Some(texture); self.is_suboptimal = false; },
-            Suboptimal(texture) => { self.frame = Some(texture); self.is_suboptimal = true; },
-            Timeout => bail!("SurfaceTexture: Timeout"),
-            Occluded => bail!("SurfaceTexture: Occluded"),
-            Outdated => bail!("SurfaceTexture: Outdated"),
-            Lost => bail!("SurfaceTexture: Lost"),
-            Validation => bail!("SurfaceTexture: Validation"),
+        self.view_format = if view_format == format || self.config.view_formats.contains(&view_format) { view_format } else {
+            log::warn!("view format {view_format:?} not supported, using {format:?}");
+            format
         };
 
-        Ok(())
+        self.color_space = ColorSpace::from_format(self.view_format);
+    }
+
+    pub fn set_present_modes(&mut self, adapter: &wgpu::Adapter, modes: &[PresentMode]) {
+        self.set_options(adapter, self.options.clone().present_modes(modes));
+    }
+
+    pub fn reconfigure(&mut self) {
+        let device = self.device.clone();
+        self.configure(&device, self.depth_testing());
+    }
+
+
+    // attachments are only rebuilt if the size changed
+    fn recover(&mut self, size: [u32; 2]) {
+        if size == self.size() {
+            self.frame = None;
+            self.surface.configure(&self.device, &self.config);
+        } else {
+            let device = self.device.clone();
+            self.update(&device, size);
+        }
+    }
+
+
+    // recovers with the configured size, see request_frame_sized
+    pub fn request_frame(&mut self) -> FrameStatus {
+        self.request_frame_sized(self.size())
+    }
+
+    // size is the current surface size, e.g. the window's inner size, used when recovering
+    pub fn request_frame_sized(&mut self, size: impl Into<[u32; 2]>) -> FrameStatus {
+
+        use wgpu::CurrentSurfaceTexture::*;
+
+        let size = size.into();
+
+        let mut retries = 0;
+
+        loop {
+            let can_retry = retries < self.recovery.max_retries;
+
+            let status = match self.surface.get_current_texture() {
+                Success(texture) => {
+                    self.frame = Some(texture); self.is_suboptimal = false;
+                    if retries == 0 { FrameStatus::Ready } else { FrameStatus::Reconfigured }
+                },
+                Suboptimal(texture) => if self.recovery.reconfigure_suboptimal && !self.suboptimal_reconfigured && can_retry {
+                    drop(texture);
+                    self.suboptimal_reconfigured = true;
+                    self.recover(size);
+                    retries += 1;
+                    continue;
+                } else {
+                    self.frame = Some(texture); self.is_suboptimal = true;
+                    FrameStatus::Suboptimal
+                },
+                Outdated | Lost if can_retry => {
+                    self.recover(size);
+                    retries += 1;
+                    continue;
+                },
+                Timeout => FrameStatus::Timeout,
+                Occluded => FrameStatus::Occluded,
+                Outdated => FrameStatus::Outdated,
+                Lost => FrameStatus::Lost,
+                Validation => FrameStatus::Validation,
+            };
+
+            // skipping is expected while the window is hidden
+            if status.is_skipped() { log::debug!("{status}") }
+            else if !status.has_frame() { log::warn!("{status}") }
+
+            return status;
+        }
     }
 
 
@@ -237,12 +574,15 @@ impl SurfaceTarget {
     }
 
 
+    // skipped frames return Ok(None), unrecoverable states the FrameStatus
     pub fn with_frame<T: ImplicitControlFlow>(
         &mut self, dsc: Option<&wgpu::TextureViewDescriptor>, handler: impl FnOnce(&SurfaceFrame) -> T
-    ) -> Res<T>
+    ) -> Result<Option<T>, FrameStatus>
     {
         if self.frame.is_none() {
-            self.request_frame()?;
+            let status = self.request_frame();
+            if status.is_skipped() { return Ok(None) }
+            if !status.has_frame() { return Err(status) }
         }
 
         let res = self.handle_surface_frame(dsc, handler).unwrap();
@@ -253,7 +593,7 @@ impl SurfaceTarget {
             frame.present();
         }
 
-        Ok(res)
+        Ok(Some(res))
     }
 }
 
@@ -308,3 +648,116 @@ impl TextureTarget {
         }
     }
 }
+
+
+impl Resizable for SurfaceTarget {
+    fn resize(&mut self, gx: &impl WgxDevice, size: impl Into<[u32; 2]>) {
+        self.update(gx, size);
+    }
+}
+
+impl Resizable for TextureTarget {
+    fn resize(&mut self, gx: &impl WgxDevice, size: impl Into<[u32; 2]>) {
+        let size = size.into();
+
+        self.descriptor.set_size_2d(size);
+        self.texture = gx.texture(&self.descriptor);
+        self.view = self.texture.create_view(&self.descriptor.default_view());
+
+        if let Some(msaa) = self.msaa_opt.as_mut() { msaa.resize(gx, size); }
+        if let Some(depth) = self.depth_opt.as_mut() { depth.resize(gx, size); }
+    }
+}
+
+
+// multiple render targets
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MrtSlot {
+    pub format: TextureFormat,
+    pub view_format: Option<TextureFormat>,
+    pub usage: TexUse,
+    pub resolve: bool, // resolve multisampled rendering into a single sampled texture
+}
+
+impl MrtSlot {
+    pub fn new(format: TextureFormat, usage: TexUse) -> Self {
+        Self { format, view_format: None, usage, resolve: true }
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct MrtTarget<const N: usize> {
+    pub colors: [TextureLot; N],
+    pub msaa: u32,
+    pub msaa_opts: [Option<TextureLot>; N],
+    pub depth_opt: Option<TextureLot>,
+}
+
+impl<const N: usize> RenderTarget for MrtTarget<N> {
+    fn size(&self) -> [u32; 2] { self.colors[0].descriptor.size_2d() }
+    fn msaa(&self) -> u32 { self.msaa }
+    fn depth_testing(&self) -> Option<TextureFormat> { self.depth_opt.as_ref().map(|d| d.format()) }
+    fn format(&self) -> TextureFormat { self.colors[0].format() }
+    fn color_formats(&self) -> Vec<Option<TextureFormat>> { self.colors.iter().map(|c| Some(c.format())).collect() }
+}
+
+impl<const N: usize> MrtTarget<N> {
+
+    pub fn new(gx:&impl WgxDevice, size:impl Into<[u32; 2]>, msaa:u32, depth_testing:Option<TextureFormat>, slots: [MrtSlot; N]) -> Self
+    {
+        const { assert!(N > 0, "MrtTarget needs at least one color slot") };
+
+        let [w, h] = size.into();
+        let resolves = |slot: &MrtSlot| msaa > 1 && slot.resolve;
+
+        Self {
+            colors: slots.map(|slot| TextureLot::new_2d(
+                gx, [w, h, 1], if resolves(&slot) { 1 } else { msaa },
+                slot.format, slot.view_format, slot.usage | TexUse::RENDER_ATTACHMENT,
+            )),
+
+            msaa_opts: slots.map(|slot| resolves(&slot).then(||
+                TextureLot::new_2d(gx, [w, h, 1], msaa, slot.format, slot.view_format, TexUse::RENDER_ATTACHMENT)
+            )),
+
+            msaa,
+
+            depth_opt: depth_testing.map(|depth_format|
+                TextureLot::new_2d(gx, [w, h, 1], msaa, depth_format, None, TexUse::RENDER_ATTACHMENT)
+            ),
+        }
+    }
+
+    pub fn color_attachment(&self, slot: usize, clear_color: Option<crate::Color>) -> ColorAttachment<'_> {
+        let color = &self.colors[slot];
+        ColorAttachment {
+            view: &color.view, format: color.format(), clear: clear_color,
+            msaa: self.msaa_opts[slot].as_ref().map(|o| &o.view),
+        }
+    }
+
+    pub fn depth_attachment(&self, clear_depth: Option<f32>, clear_stencil: Option<u32>) -> Option<DepthAttachment<'_>> {
+        self.depth_opt.as_ref().map(|d| DepthAttachment { view: &d.view, format: d.format(), clear_depth, clear_stencil })
+    }
+
+    pub fn attachments(&self, clear_colors: [Option<crate::Color>; N], clear_depth: Option<f32>, clear_stencil: Option<u32>) -> RenderAttachments<'_, N> {
+        let mut slot = 0;
+        let colors = clear_colors.map(|clear_color| {
+            let attachment = self.color_attachment(slot, clear_color).into();
+            slot += 1;
+            Some(attachment)
+        });
+        (colors, self.depth_attachment(clear_depth, clear_stencil).map(|a| a.into()))
+    }
+}
+
+impl<const N: usize> Resizable for MrtTarget<N> {
+    fn resize(&mut self, gx: &impl WgxDevice, size: impl Into<[u32; 2]>) {
+        let size = size.into();
+        for color in &mut self.colors { color.resize(gx, size); }
+        for msaa in self.msaa_opts.iter_mut().flatten() { msaa.resize(gx, size); }
+        if let Some(depth) = self.depth_opt.as_mut() { depth.resize(gx, size); }
+    }
+}
@@ -0,0 +1,315 @@
+use std::num::NonZeroU64;
+use wgpu::{
+    BindGroupLayoutEntry, BindingType, BufferBindingType, TextureSampleType, StorageTextureAccess, SamplerBindingType,
+    naga::{
+        self, AddressSpace, ImageClass, ImageDimension, ScalarKind, StorageAccess, StorageFormat, TypeInner, ArraySize,
+        proc::Layouter, valid::{Validator, ValidationFlags, Capabilities},
+    },
+};
+use crate::*;
+use anyhow::{Result as Res, bail, Context};
+
+
+// bind group layout entries and immediate data size derived from the globals used by entry points,
+// textures are reflected as filterable unless multisampled, entries may be adjusted before creating layouts
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShaderReflection {
+    pub groups: Vec<Vec<BindGroupLayoutEntry>>, // by group index, sorted by binding
+    pub immediate_size: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReflectedLayout {
+    pub bind_groups: Vec<Option<wgpu::BindGroupLayout>>, // None for groups without entries
+    pub pipeline: wgpu::PipelineLayout,
+}
+
+
+impl ShaderReflection {
+
+    // reflects all entry points if entry_points is empty
+    pub fn new(module: &naga::Module, entry_points: &[&str]) -> Res<Self> {
+
+        let info = Validator::new(ValidationFlags::all(), Capabilities::all()).validate(module)
+            .context("invalid shader module")?;
+
+        let mut layouter = Layouter::default();
+        layouter.update(module.to_ctx())?;
+
+        for name in entry_points {
+            if !module.entry_points.iter().any(|entry| entry.name == *name) {
+                bail!("entry point {name} not found");
+            }
+        }
+
+        let mut reflection = Self::default();
+
+        for (index, entry) in module.entry_points.iter().enumerate() {
+
+            if !entry_points.is_empty() && !entry_points.contains(&entry.name.as_str()) { continue }
+
+            let visibility = match entry.stage {
+                naga::ShaderStage::Vertex => Stage::VERTEX,
+                naga::ShaderStage::Fragment => Stage::FRAGMENT,
+                naga::ShaderStage::Compute => Stage::COMPUTE,
+                naga::ShaderStage::Task => Stage::TASK,
+                naga::ShaderStage::Mesh => Stage::MESH,
+                naga::ShaderStage::RayGeneration => Stage::RAY_GENERATION,
+                naga::ShaderStage::Miss => Stage::MISS,
+                naga::ShaderStage::AnyHit => Stage::ANY_HIT,
+                naga::ShaderStage::ClosestHit => Stage::CLOSEST_HIT,
+            };
+
+            let usage = info.get_entry_point(index);
+
+            for (handle, var) in module.global_variables.iter() {
+
+                if usage[handle].is_empty() { continue }
+
+                if var.space == AddressSpace::Immediate {
+                    reflection.immediate_size = reflection.immediate_size.max(layouter[var.ty].size);
+                    continue;
+                }
+
+                let Some(binding) = &var.binding else { continue };
+
+                // binding arrays take the type of their elements
+                let (ty, count) = match module.types[var.ty].inner {
+                    TypeInner::BindingArray { base, size: ArraySize::Constant(count) } => (base, Some(count)),
+                    TypeInner::BindingArray { .. } => {
+                        bail!("binding array {} needs a constant size", var.name.as_deref().unwrap_or("?"));
+                    },
+                    _ => (var.ty, None),
+                };
+
+                let ty = binding_type(module, &layouter, var.space, ty)
+                    .with_context(|| format!("global {} at binding {}", var.name.as_deref().unwrap_or("?"), binding.binding))?;
+
+                reflection.insert(binding.group, BindGroupLayoutEntry { binding: binding.binding, visibility, ty, count })?;
+            }
+        }
+
+        Ok(reflection)
+    }
+
+    fn insert(&mut self, group: u32, entry: BindGroupLayoutEntry) -> Res<()> {
+
+        if self.groups.len() <= group as usize {
+            self.groups.resize(group as usize + 1, Vec::new());
+        }
+
+        let entries = &mut self.groups[group as usize];
+
+        match entries.binary_search_by_key(&entry.binding, |entry| entry.binding) {
+            Ok(i) => {
+                let existing = &mut entries[i];
+                if existing.ty != entry.ty || existing.count != entry.count {
+                    bail!("conflicting types at group {group} binding {}: {:?} and {:?}", entry.binding, existing.ty, entry.ty);
+                }
+                existing.visibility |= entry.visibility;
+            },
+            Err(i) => entries.insert(i, entry),
+        }
+
+        Ok(())
+    }
+
+    // combine with the reflection of another module, e.g. for separate vertex and fragment shaders
+    pub fn merge(mut self, other: &Self) -> Res<Self> {
+        for (group, entries) in other.groups.iter().enumerate() {
+            for entry in entries {
+                self.insert(group as u32, *entry)?;
+            }
+        }
+        self.immediate_size = self.immediate_size.max(other.immediate_size);
+        Ok(self)
+    }
+
+    pub fn bind_group_layouts(&self, gx: &impl WgxDevice) -> Vec<Option<wgpu::BindGroupLayout>> {
+        self.groups.iter().map(|entries| (!entries.is_empty()).then(|| gx.layout(entries))).collect()
+    }
+
+    pub fn layout(&self, gx: &impl WgxDevice) -> ReflectedLayout {
+        let bind_groups = self.bind_group_layouts(gx);
+        let bind_group_refs: Vec<_> = bind_groups.iter().map(Option::as_ref).collect();
+        let pipeline = gx.pipeline_layout(self.immediate_size, &bind_group_refs);
+        ReflectedLayout { bind_groups, pipeline }
+    }
+}
+
+
+fn binding_type(module: &naga::Module, layouter: &Layouter, space: AddressSpace, ty: naga::Handle<naga::Type>) -> Res<BindingType> {
+
+    let min_binding_size = NonZeroU64::new(layouter[ty].size as u64);
+
+    Ok(match space {
+
+        AddressSpace::Uniform => BindingType::Buffer {
+            ty: BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size,
+        },
+
+        AddressSpace::Storage { access } => BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: !access.contains(StorageAccess::STORE) },
+            has_dynamic_offset: false, min_binding_size,
+        },
+
+        AddressSpace::Handle => match module.types[ty].inner {
+
+            TypeInner::Sampler { comparison: true } => BindingType::Sampler(SamplerBindingType::Comparison),
+            TypeInner::Sampler { comparison: false } => BindingType::Sampler(SamplerBindingType::Filtering),
+
+            TypeInner::Image { dim, arrayed, class } => {
+
+                let view_dimension = match (dim, arrayed) {
+                    (ImageDimension::D1, _) => ViewDimension::D1,
+                    (ImageDimension::D2, false) => ViewDimension::D2,
+                    (ImageDimension::D2, true) => ViewDimension::D2Array,
+                    (ImageDimension::D3, _) => ViewDimension::D3,
+                    (ImageDimension::Cube, false) => ViewDimension::Cube,
+                    (ImageDimension::Cube, true) => ViewDimension::CubeArray,
+                };
+
+                match class {
+                    ImageClass::Sampled { kind, multi } => BindingType::Texture {
+                        sample_type: match kind {
+                            ScalarKind::Float => TextureSampleType::Float { filterable: !multi },
+                            ScalarKind::Sint => TextureSampleType::Sint,
+                            ScalarKind::Uint => TextureSampleType::Uint,
+                            kind => bail!("unsupported texture sample kind {kind:?}"),
+                        },
+                        view_dimension, multisampled: multi,
+                    },
+                    ImageClass::Depth { multi } => BindingType::Texture {
+                        sample_type: TextureSampleType::Depth, view_dimension, multisampled: multi,
+                    },
+                    ImageClass::Storage { format, access } => BindingType::StorageTexture {
+                        access: if access.contains(StorageAccess::ATOMIC) { StorageTextureAccess::Atomic }
+                            else if access.contains(StorageAccess::LOAD | StorageAccess::STORE) { StorageTextureAccess::ReadWrite }
+                            else if access.contains(StorageAccess::STORE) { StorageTextureAccess::WriteOnly }
+                            else { StorageTextureAccess::ReadOnly },
+                        format: storage_format(format),
+                        view_dimension,
+                    },
+                    ImageClass::External => BindingType::ExternalTexture,
+                }
+            },
+
+            TypeInner::AccelerationStructure { vertex_return } => BindingType::AccelerationStructure { vertex_return },
+
+            ref inner => bail!("unsupported handle type {inner:?}"),
+        },
+
+        space => bail!("address space {space:?} has no bindings"),
+    })
+}
+
+
+fn storage_format(format: StorageFormat) -> TexFmt {
+    match format {
+        StorageFormat::R8Unorm => TexFmt::R8Unorm,
+        StorageFormat::R8Snorm => TexFmt::R8Snorm,
+        StorageFormat::R8Uint => TexFmt::R8Uint,
+        StorageFormat::R8Sint => TexFmt::R8Sint,
+        StorageFormat::R16Uint => TexFmt::R16Uint,
+        StorageFormat::R16Sint => TexFmt::R16Sint,
+        StorageFormat::R16Float => TexFmt::R16Float,
+        StorageFormat::Rg8Unorm => TexFmt::Rg8Unorm,
+        StorageFormat::Rg8Snorm => TexFmt::Rg8Snorm,
+        StorageFormat::Rg8Uint => TexFmt::Rg8Uint,
+        StorageFormat::Rg8Sint => TexFmt::Rg8Sint,
+        StorageFormat::R32Uint => TexFmt::R32Uint,
+        StorageFormat::R32Sint => TexFmt::R32Sint,
+        StorageFormat::R32Float => TexFmt::R32Float,
+        StorageFormat::Rg16Uint => TexFmt::Rg16Uint,
+        StorageFormat::Rg16Sint => TexFmt::Rg16Sint,
+        StorageFormat::Rg16Float => TexFmt::Rg16Float,
+        StorageFormat::Rgba8Unorm => TexFmt::Rgba8Unorm,
+        StorageFormat::Rgba8Snorm => TexFmt::Rgba8Snorm,
+        StorageFormat::Rgba8Uint => TexFmt::Rgba8Uint,
+        StorageFormat::Rgba8Sint => TexFmt::Rgba8Sint,
+        StorageFormat::Bgra8Unorm => TexFmt::Bgra8Unorm,
+        StorageFormat::Rgb10a2Uint => TexFmt::Rgb10a2Uint,
+        StorageFormat::Rgb10a2Unorm => TexFmt::Rgb10a2Unorm,
+        StorageFormat::Rg11b10Ufloat => TexFmt::Rg11b10Ufloat,
+        StorageFormat::R64Uint => TexFmt::R64Uint,
+        StorageFormat::Rg32Uint => TexFmt::Rg32Uint,
+        StorageFormat::Rg32Sint => TexFmt::Rg32Sint,
+        StorageFormat::Rg32Float => TexFmt::Rg32Float,
+        StorageFormat::Rgba16Uint => TexFmt::Rgba16Uint,
+        StorageFormat::Rgba16Sint => TexFmt::Rgba16Sint,
+        StorageFormat::Rgba16Float => TexFmt::Rgba16Float,
+        StorageFormat::Rgba32Uint => TexFmt::Rgba32Uint,
+        StorageFormat::Rgba32Sint => TexFmt::Rgba32Sint,
+        StorageFormat::Rgba32Float => TexFmt::Rgba32Float,
+        StorageFormat::R16Unorm => TexFmt::R16Unorm,
+        StorageFormat::R16Snorm => TexFmt::R16Snorm,
+        StorageFormat::Rg16Unorm => TexFmt::Rg16Unorm,
+        StorageFormat::Rg16Snorm => TexFmt::Rg16Snorm,
+        StorageFormat::Rgba16Unorm => TexFmt::Rgba16Unorm,
+        StorageFormat::Rgba16Snorm => TexFmt::Rgba16Snorm,
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SOURCE: &str = "
+        struct Uniforms { transform: mat4x4f, color: vec4f };
+        struct Particles { count: u32, items: array<vec4f> };
+
+        @group(0) @binding(0) var<uniform> uniforms: Uniforms;
+        @group(0) @binding(1) var color_texture: texture_2d<f32>;
+        @group(0) @binding(2) var color_sampler: sampler;
+        @group(2) @binding(0) var<storage, read_write> particles: Particles;
+        @group(2) @binding(1) var output: texture_storage_2d<rgba8unorm, write>;
+
+        @vertex fn vs_main(@location(0) position: vec3f) -> @builtin(position) vec4f {
+            return uniforms.transform * vec4f(position, 1.0);
+        }
+
+        @fragment fn fs_main() -> @location(0) vec4f {
+            return uniforms.color * textureSample(color_texture, color_sampler, vec2f(0.5));
+        }
+
+        @compute @workgroup_size(64) fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+            particles.items[id.x] = vec4f(f32(particles.count));
+            textureStore(output, id.xy, vec4f(1.0));
+        }
+    ";
+
+    #[test]
+    fn reflection() {
+        let module = naga::front::wgsl::parse_str(SOURCE).unwrap();
+
+        let render = ShaderReflection::new(&module, &["vs_main", "fs_main"]).unwrap();
+        assert_eq!(render.groups.len(), 1);
+
+        let [uniforms, texture, sampler] = render.groups[0].as_slice() else { panic!("expected 3 entries") };
+        assert_eq!(uniforms.visibility, Stage::VERTEX_FRAGMENT);
+        assert_eq!(uniforms.ty, BindingType::Buffer {
+            ty: BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: NonZeroU64::new(80),
+        });
+        assert_eq!((texture.visibility, texture.ty), (Stage::FRAGMENT, BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: true }, view_dimension: ViewDimension::D2, multisampled: false,
+        }));
+        assert_eq!(sampler.ty, BindingType::Sampler(SamplerBindingType::Filtering));
+
+        let compute = ShaderReflection::new(&module, &["cs_main"]).unwrap();
+        assert_eq!((compute.groups.len(), compute.groups[0].len()), (3, 0));
+
+        let [particles, output] = compute.groups[2].as_slice() else { panic!("expected 2 entries") };
+        assert_eq!(particles.ty, BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: false }, has_dynamic_offset: false, min_binding_size: NonZeroU64::new(32),
+        });
+        assert_eq!(output.ty, BindingType::StorageTexture {
+            access: StorageTextureAccess::WriteOnly, format: TexFmt::Rgba8Unorm, view_dimension: ViewDimension::D2,
+        });
+
+        let all = ShaderReflection::new(&module, &[]).unwrap();
+        assert_eq!(all, render.merge(&compute).unwrap());
+
+        assert!(ShaderReflection::new(&module, &["main"]).is_err());
+    }
+}
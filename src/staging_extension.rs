@@ -1,5 +1,5 @@
 
-use std::{sync::mpsc::sync_channel, ops::RangeBounds};
+use std::{sync::{Arc, Mutex, mpsc::sync_channel}, ops::RangeBounds, task::{Poll, Waker}};
 use wgpu::{Buffer, BufferSlice, BufferAddress, BufferSize, BufferViewMut, WriteOnly, util::StagingBelt, CommandEncoder};
 use crate::{*};
 use anyhow::{Result as Res};
@@ -65,7 +65,7 @@ impl StagingBeltExtension for StagingBelt {
     &mut self, encoder: &mut CommandEncoder, target: &Buffer, byte_offset: BufferAddress, data: T,
   ) {
     let bytes = data.read_bytes();
-    self.stage(encoder, target, byte_offset..(bytes.len() as u64)).copy_from_slice(bytes);
+    self.stage(encoder, target, byte_offset..(byte_offset + bytes.len() as u64)).copy_from_slice(bytes);
   }
 
   fn write_iter<T: AsBytes, I: Iterator<Item=T>>(
@@ -145,4 +145,26 @@ impl WithMapSync for BufferSlice<'_> {
 
     Ok(res)
   }
-}
\ No newline at end of file
+}
+
+
+// resolves when the slice is mapped, on native the device has to be polled for that to happen
+pub fn map_async(slice: &BufferSlice, mode: MapMode) -> impl Future<Output=Res<()>> + Send + use<> {
+
+  let state = Arc::new(Mutex::new((None, None::<Waker>)));
+
+  let callback_state = state.clone();
+  slice.map_async(mode, move |result| {
+    let (res, waker) = &mut *callback_state.lock().unwrap();
+    *res = Some(result);
+    if let Some(waker) = waker.take() { waker.wake() }
+  });
+
+  std::future::poll_fn(move |cx| {
+    let (res, waker) = &mut *state.lock().unwrap();
+    match res.take() {
+      Some(result) => Poll::Ready(result.map_err(Into::into)),
+      None => { *waker = Some(cx.waker().clone()); Poll::Pending },
+    }
+  })
+}
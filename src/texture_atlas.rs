@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use wgpu::TextureFormat;
+use crate::*;
+use anyhow::{Result as Res, bail};
+
+
+// shelf packer
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    free: Vec<[u32; 2]>, // sorted free spans along x, start and end
+}
+
+impl Shelf {
+    fn insert_free(&mut self, span: [u32; 2]) {
+        let i = self.free.partition_point(|free| free[0] < span[0]);
+        self.free.insert(i, span);
+
+        // merge with neighbours
+        if i + 1 < self.free.len() && self.free[i][1] == self.free[i + 1][0] {
+            self.free[i][1] = self.free.remove(i + 1)[1];
+        }
+        if i > 0 && self.free[i - 1][1] == self.free[i][0] {
+            self.free[i - 1][1] = self.free.remove(i)[1];
+        }
+    }
+}
+
+
+// packs rectangles into rows (shelves) of similar height
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShelfPacker {
+    size: [u32; 2],
+    shelves: Vec<Shelf>,
+    used_height: u32,
+}
+
+impl ShelfPacker {
+
+    pub fn new(size: [u32; 2]) -> Self {
+        Self { size, shelves: Vec::new(), used_height: 0 }
+    }
+
+    pub fn size(&self) -> [u32; 2] { self.size }
+
+    pub fn is_empty(&self) -> bool { self.shelves.is_empty() }
+
+    pub fn clear(&mut self) {
+        self.shelves.clear();
+        self.used_height = 0;
+    }
+
+    // returns the origin
+    pub fn allocate(&mut self, [width, height]: [u32; 2]) -> Option<[u32; 2]> {
+
+        let (width, height) = (width.max(1), height.max(1));
+
+        // best fitting free span, least wasted height
+        let mut best: Option<(usize, usize, u32)> = None;
+
+        for (i, shelf) in self.shelves.iter().enumerate() {
+            if shelf.height < height { continue }
+            let waste = shelf.height - height;
+            if best.is_some_and(|(.., best_waste)| best_waste <= waste) { continue }
+
+            if let Some(j) = shelf.free.iter().position(|free| free[1] - free[0] >= width) {
+                best = Some((i, j, waste));
+            }
+        }
+
+        // prefer a new shelf over wasting more than half the height
+        let new_shelf = self.used_height + height <= self.size[1] && width <= self.size[0];
+
+        let (i, j) = match best {
+            Some((i, j, waste)) if !(new_shelf && waste > height / 2) => (i, j),
+            _ if new_shelf => {
+                self.shelves.push(Shelf { y: self.used_height, height, free: vec![[0, self.size[0]]] });
+                self.used_height += height;
+                (self.shelves.len() - 1, 0)
+            },
+            _ => return None,
+        };
+
+        let shelf = &mut self.shelves[i];
+        let free = &mut shelf.free[j];
+        let origin = [free[0], shelf.y];
+
+        free[0] += width;
+        if free[0] == free[1] { shelf.free.remove(j); }
+
+        Some(origin)
+    }
+
+    pub fn deallocate(&mut self, [x, y]: [u32; 2], [width, _]: [u32; 2]) {
+
+        let Some(shelf) = self.shelves.iter_mut().find(|shelf| shelf.y == y) else { return };
+        shelf.insert_free([x, x + width.max(1)]);
+
+        // release empty shelves from the end
+        while let Some(shelf) = self.shelves.last() && shelf.free == [[0, self.size[0]]] {
+            self.used_height -= shelf.height;
+            self.shelves.pop();
+        }
+    }
+
+    // existing allocations keep their position
+    pub fn grow(&mut self, size: [u32; 2]) {
+        assert!(size[0] >= self.size[0] && size[1] >= self.size[1], "packer can't shrink");
+
+        if size[0] > self.size[0] {
+            for shelf in &mut self.shelves {
+                shelf.insert_free([self.size[0], size[0]]);
+            }
+        }
+
+        self.size = size;
+    }
+}
+
+
+// texture atlas
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AtlasId(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion {
+    pub id: AtlasId,
+    pub origin: [u32; 2],
+    pub size: [u32; 2],
+    pub uv: [[f32; 2]; 2], // min, max, changes when the atlas grows
+}
+
+
+#[derive(Debug)]
+pub struct TextureAtlas {
+    pub texture: TextureLot,
+    packer: ShelfPacker,
+    regions: HashMap<AtlasId, ([u32; 2], [u32; 2])>,
+    next_id: u32,
+    padding: u32, // space between regions against filtering bleed
+    generation: u64,
+}
+
+impl TextureAtlas {
+
+    pub fn new(gx: &impl WgxDevice, size: [u32; 2], format: TextureFormat, view_format: Option<TextureFormat>, usage: TexUse) -> Self {
+        let usage = usage | TexUse::TEXTURE_BINDING | TexUse::COPY_DST | TexUse::COPY_SRC;
+        Self {
+            texture: TextureLot::new_2d(gx, [size[0], size[1], 1], 1, format, view_format, usage),
+            packer: ShelfPacker::new(size),
+            regions: HashMap::new(),
+            next_id: 0,
+            padding: 1,
+            generation: 0,
+        }
+    }
+
+    pub fn padding(mut self, padding: u32) -> Self { self.padding = padding; self }
+
+    pub fn size(&self) -> [u32; 2] { self.packer.size() }
+
+    pub fn len(&self) -> usize { self.regions.len() }
+
+    pub fn is_empty(&self) -> bool { self.regions.is_empty() }
+
+    // changes whenever the texture is reallocated, bind groups and uvs have to be updated then
+    pub fn generation(&self) -> u64 { self.generation }
+
+    fn to_region(&self, id: AtlasId, origin: [u32; 2], size: [u32; 2]) -> AtlasRegion {
+        let [width, height] = self.size().map(|v| v as f32);
+        let uv = |[x, y]: [u32; 2]| [x as f32 / width, y as f32 / height];
+        AtlasRegion { id, origin, size, uv: [uv(origin), uv([origin[0] + size[0], origin[1] + size[1]])] }
+    }
+
+    pub fn region(&self, id: AtlasId) -> Option<AtlasRegion> {
+        self.regions.get(&id).map(|&(origin, size)| self.to_region(id, origin, size))
+    }
+
+    pub fn regions(&self) -> impl Iterator<Item=AtlasRegion> + '_ {
+        self.regions.iter().map(|(&id, &(origin, size))| self.to_region(id, origin, size))
+    }
+
+    // reserves space, grows the atlas up to the device limit if needed
+    pub fn allocate(&mut self, gx: &impl WgxDeviceQueue, size: [u32; 2]) -> Res<AtlasRegion> {
+
+        let padded = size.map(|v| v + self.padding);
+
+        let origin = loop {
+            if let Some(origin) = self.packer.allocate(padded) { break origin }
+
+            let max = gx.device().limits().max_texture_dimension_2d;
+            let [width, height] = self.size();
+
+            if width >= max && height >= max {
+                bail!("texture atlas is full at {width}x{height}");
+            }
+
+            let grown = if width <= height { [(width * 2).min(max), height] } else { [width, (height * 2).min(max)] };
+            self.grow(gx, grown);
+        };
+
+        let id = AtlasId(self.next_id);
+        self.next_id += 1;
+        self.regions.insert(id, (origin, size));
+
+        Ok(self.to_region(id, origin, size))
+    }
+
+    // tightly packed data
+    pub fn insert<T: ReadBytes>(&mut self, gx: &impl WgxDeviceQueue, size: [u32; 2], data: T) -> Res<AtlasRegion> {
+        let region = self.allocate(gx, size)?;
+        self.write(gx, &region, data);
+        Ok(region)
+    }
+
+    pub fn write<T: ReadBytes>(&self, gx: &impl WgxQueue, region: &AtlasRegion, data: T) {
+        let [width, height] = region.size;
+        gx.write_texture(
+            (&self.texture.texture, 0, [region.origin[0], region.origin[1], 0]),
+            (data, (0, (self.texture.descriptor.format, width), None)),
+            [width, height, 1],
+        );
+    }
+
+    // evict a region, its space can be reused
+    pub fn remove(&mut self, id: AtlasId) -> Option<AtlasRegion> {
+        let (origin, size) = self.regions.remove(&id)?;
+        self.packer.deallocate(origin, size.map(|v| v + self.padding));
+        Some(self.to_region(id, origin, size))
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&AtlasRegion) -> bool) {
+        let evicted: Vec<_> = self.regions().filter(|region| !keep(region)).map(|region| region.id).collect();
+        for id in evicted { self.remove(id); }
+    }
+
+    pub fn clear(&mut self) {
+        self.regions.clear();
+        self.packer.clear();
+    }
+
+    // reallocates the texture and copies the contents of every mip level and layer
+    pub fn grow(&mut self, gx: &impl WgxDeviceQueue, size: [u32; 2]) {
+
+        let old = self.texture.descriptor;
+        let mut descriptor = old;
+        descriptor.set_size_2d(size);
+        let texture = TextureLot::new(gx, descriptor);
+
+        gx.with_encoder(|encoder| {
+            for mip in 0..old.mip_level_count {
+                let [width, height, _] = old.mip_size(mip);
+                encoder.copy_texture_to_texture(
+                    (&self.texture.texture, mip, [0, 0, 0]).to(),
+                    (&texture.texture, mip, [0, 0, 0]).to(),
+                    ToExtent3d::to([width, height, old.array_layer_count()]),
+                );
+            }
+        });
+
+        self.texture = texture;
+        self.packer.grow(size);
+        self.generation += 1;
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shelf_packer() {
+        let mut packer = ShelfPacker::new([16, 16]);
+
+        let a = packer.allocate([8, 4]).unwrap();
+        let b = packer.allocate([8, 4]).unwrap();
+        let c = packer.allocate([4, 8]).unwrap();
+
+        assert_eq!([a, b, c], [[0, 0], [8, 0], [0, 4]]);
+        assert_eq!(packer.allocate([16, 8]), None);
+
+        // freed space is reused
+        packer.deallocate(a, [8, 4]);
+        assert_eq!(packer.allocate([8, 3]), Some([0, 0]));
+
+        // trailing empty shelves are released
+        packer.deallocate(c, [4, 8]);
+        assert_eq!(packer.allocate([16, 12]), Some([0, 4]));
+
+        // growing keeps existing allocations
+        packer.grow([32, 16]);
+        assert_eq!(packer.allocate([16, 4]), Some([16, 0]));
+    }
+}
@@ -2,6 +2,7 @@
 use std::slice;
 use wgpu::{TextureFormat, TextureDimension, TextureViewDimension, TextureViewDescriptor, TextureAspect};
 use crate::*;
+use anyhow::{Result as Res, bail};
 
 
 // extend Texture
@@ -61,18 +62,48 @@ pub struct TexDsc {
 
 
 impl TexDsc {
-    pub fn new_2d(
-        size: [u32; 3], sample_count: u32,
+    pub fn new(
+        view_dimension: TextureViewDimension, size: [u32; 3], sample_count: u32,
         format: TextureFormat, view_format: Option<TextureFormat>, usage: TexUse,
     ) -> Self {
         Self {
-            label: None, size, mip_level_count: 1, sample_count, usage,
-            view_dimension: dimension_to_view(TextureDimension::D2, size[2]),
+            label: None, size, mip_level_count: 1, sample_count, usage, view_dimension,
             view_aspect: TextureAspect::All,
             format, view_format: view_format.unwrap_or(format),
         }
     }
+    pub fn new_2d(
+        size: [u32; 3], sample_count: u32,
+        format: TextureFormat, view_format: Option<TextureFormat>, usage: TexUse,
+    ) -> Self {
+        Self::new(dimension_to_view(TextureDimension::D2, size[2]), size, sample_count, format, view_format, usage)
+    }
+    pub fn new_1d(width: u32, format: TextureFormat, view_format: Option<TextureFormat>, usage: TexUse) -> Self {
+        Self::new(TextureViewDimension::D1, [width, 1, 1], 1, format, view_format, usage)
+    }
+    // array view even with one layer
+    pub fn new_2d_array(size: [u32; 3], format: TextureFormat, view_format: Option<TextureFormat>, usage: TexUse) -> Self {
+        Self::new(TextureViewDimension::D2Array, size, 1, format, view_format, usage)
+    }
+    pub fn new_cube(size: u32, format: TextureFormat, view_format: Option<TextureFormat>, usage: TexUse) -> Self {
+        Self::new(TextureViewDimension::Cube, [size, size, 6], 1, format, view_format, usage)
+    }
+    pub fn new_cube_array(size: u32, count: u32, format: TextureFormat, view_format: Option<TextureFormat>, usage: TexUse) -> Self {
+        Self::new(TextureViewDimension::CubeArray, [size, size, 6 * count], 1, format, view_format, usage)
+    }
+    pub fn new_3d(size: [u32; 3], format: TextureFormat, view_format: Option<TextureFormat>, usage: TexUse) -> Self {
+        Self::new(TextureViewDimension::D3, size, 1, format, view_format, usage)
+    }
+
     pub fn srgb(&self) -> bool { self.view_format.is_srgb() }
+    pub fn array_layer_count(&self) -> u32 {
+        if self.view_dimension == TextureViewDimension::D3 { 1 } else { self.size[2] }
+    }
+    pub fn max_mip_level_count(&self) -> u32 {
+        let [width, height, depth] = self.size;
+        let extent = if self.view_dimension == TextureViewDimension::D3 { width.max(height).max(depth) } else { width.max(height) };
+        u32::BITS - extent.leading_zeros()
+    }
     pub fn size_2d(&self) -> [u32; 2] { [self.size[0], self.size[1]] }
     pub fn set_size_2d(&mut self, [width, height]: [u32; 2]) {
         self.size[0] = width;
@@ -89,9 +120,56 @@ impl TexDsc {
             base_mip_level: 0,
             mip_level_count: Some(self.mip_level_count),
             base_array_layer: 0,
-            array_layer_count: Some(self.size[2]),
+            array_layer_count: Some(self.array_layer_count()),
+        }
+    }
+
+    // single layer as 2d view, all mip levels
+    pub fn layer_view(&self, layer: u32) -> Res<TextureViewDescriptor<'static>> {
+        if matches!(self.view_dimension, TextureViewDimension::D1 | TextureViewDimension::D3) {
+            bail!("{:?} textures have no layers to view as 2d, render to 3d textures with a depth slice", self.view_dimension);
+        }
+        if layer >= self.array_layer_count() {
+            bail!("layer {layer} out of {} layers", self.array_layer_count());
         }
+        Ok(TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::D2),
+            base_array_layer: layer,
+            array_layer_count: Some(1),
+            ..self.default_view()
+        })
     }
+
+    // single mip level, all layers
+    pub fn mip_view(&self, mip: u32) -> TextureViewDescriptor<'static> {
+        TextureViewDescriptor {
+            base_mip_level: mip,
+            mip_level_count: Some(1),
+            ..self.default_view()
+        }
+    }
+
+    pub fn mip_layer_view(&self, mip: u32, layer: u32) -> Res<TextureViewDescriptor<'static>> {
+        Ok(TextureViewDescriptor {
+            base_mip_level: mip,
+            mip_level_count: Some(1),
+            ..self.layer_view(layer)?
+        })
+    }
+
+    pub fn mip_size(&self, mip: u32) -> [u32; 3] {
+        ToExtent3d::to(self.size).mip_level_size(mip, view_to_dimension(self.view_dimension)).to_arr()
+    }
+}
+
+
+// cube map faces in layer order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CubeFace { PositiveX, NegativeX, PositiveY, NegativeY, PositiveZ, NegativeZ }
+
+impl CubeFace {
+    pub const ALL: [Self; 6] = [Self::PositiveX, Self::NegativeX, Self::PositiveY, Self::NegativeY, Self::PositiveZ, Self::NegativeZ];
+    pub fn layer(self) -> u32 { self as u32 }
 }
 
 
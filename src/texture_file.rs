@@ -0,0 +1,515 @@
+use std::path::Path;
+use wgpu::{TextureFormat, TextureViewDimension, util::{DeviceExt, TextureDataOrder}};
+use crate::*;
+use anyhow::{Result as Res, bail, Context};
+
+
+// texture container contents, all layers and mip levels
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureFile {
+    pub descriptor: TexDsc,
+    pub order: TextureDataOrder,
+    pub data: Vec<u8>,
+}
+
+impl TextureFile {
+
+    pub fn from_bytes(bytes: &[u8]) -> Res<Self> {
+        match bytes {
+            #[cfg(feature = "ktx2")]
+            [0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, ..] => Self::from_ktx2(bytes),
+            #[cfg(feature = "dds")]
+            [b'D', b'D', b'S', b' ', ..] => Self::from_dds(bytes),
+            _ => bail!("unknown texture container format"),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Res<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::from_bytes(&bytes).with_context(|| format!("loading {}", path.display()))
+    }
+
+    #[cfg(feature = "ktx2")]
+    pub fn from_ktx2(bytes: &[u8]) -> Res<Self> {
+
+        let reader = ktx2::Reader::new(bytes).map_err(|err| anyhow::anyhow!("invalid ktx2: {err}"))?;
+        let header = reader.header();
+
+        if let Some(scheme) = header.supercompression_scheme {
+            bail!("ktx2 supercompression {scheme:?} is not supported");
+        }
+        let Some(format) = header.format.and_then(ktx2_format) else {
+            bail!("ktx2 format {:?} is not supported", header.format);
+        };
+
+        let layers = header.layer_count.max(1) * header.face_count;
+
+        let (view_dimension, size) = match () {
+            _ if header.pixel_depth > 0 => (TextureViewDimension::D3, [header.pixel_width, header.pixel_height, header.pixel_depth]),
+            // 1d arrays are loaded as 2d arrays of height 1, like with dds
+            _ if header.pixel_height == 0 && header.layer_count > 0 => (TextureViewDimension::D2Array, [header.pixel_width, 1, layers]),
+            _ if header.pixel_height == 0 => (TextureViewDimension::D1, [header.pixel_width, 1, 1]),
+            _ if header.face_count == 6 => (
+                if header.layer_count > 0 { TextureViewDimension::CubeArray } else { TextureViewDimension::Cube },
+                [header.pixel_width, header.pixel_height, layers],
+            ),
+            _ if header.layer_count > 0 => (TextureViewDimension::D2Array, [header.pixel_width, header.pixel_height, layers]),
+            _ => (TextureViewDimension::D2, [header.pixel_width, header.pixel_height, 1]),
+        };
+
+        let mut descriptor = TexDsc::new(view_dimension, size, 1, format, None, TexUse::TEXTURE_BINDING);
+        descriptor.mip_level_count = header.level_count.max(1);
+
+        // levels are stored mip major
+        let data = reader.levels().take(descriptor.mip_level_count as usize).flat_map(|level| level.data).copied().collect();
+
+        Self { descriptor, order: TextureDataOrder::MipMajor, data }.validated()
+    }
+
+    #[cfg(feature = "dds")]
+    pub fn from_dds(bytes: &[u8]) -> Res<Self> {
+        use ddsfile::{Dds, Caps2, MiscFlag, D3D10ResourceDimension};
+
+        let dds = Dds::read(bytes).map_err(|err| anyhow::anyhow!("invalid dds: {err}"))?;
+
+        let format = if let Some(format) = dds.get_dxgi_format() {
+            dxgi_format(format).with_context(|| format!("dds format {format:?} is not supported"))?
+        } else if let Some(format) = dds.get_d3d_format() {
+            d3d_format(format).with_context(|| format!("dds format {format:?} is not supported"))?
+        } else {
+            bail!("dds format is not supported");
+        };
+
+        let (width, height, depth) = (dds.get_width(), dds.get_height(), dds.get_depth());
+
+        let (cube, array_size, dim_1d) = match &dds.header10 {
+            Some(header10) => (
+                header10.misc_flag.contains(MiscFlag::TEXTURECUBE), header10.array_size.max(1),
+                header10.resource_dimension == D3D10ResourceDimension::Texture1D,
+            ),
+            None => (dds.header.caps2.contains(Caps2::CUBEMAP), 1, false),
+        };
+
+        let (view_dimension, size) = match () {
+            _ if depth > 1 => (TextureViewDimension::D3, [width, height, depth]),
+            _ if dim_1d && array_size == 1 => (TextureViewDimension::D1, [width, 1, 1]),
+            _ if cube && array_size > 1 => (TextureViewDimension::CubeArray, [width, height, 6 * array_size]),
+            _ if cube => (TextureViewDimension::Cube, [width, height, 6]),
+            _ if array_size > 1 => (TextureViewDimension::D2Array, [width, height, array_size]),
+            _ => (TextureViewDimension::D2, [width, height, 1]),
+        };
+
+        let mut descriptor = TexDsc::new(view_dimension, size, 1, format, None, TexUse::TEXTURE_BINDING);
+        descriptor.mip_level_count = dds.get_num_mipmap_levels().max(1);
+
+        Self { descriptor, order: TextureDataOrder::LayerMajor, data: dds.data }.validated()
+    }
+
+    // (layer, mip) in data order
+    fn subresources(&self) -> Vec<(u32, u32)> {
+        let (layers, mips) = (self.descriptor.array_layer_count(), self.descriptor.mip_level_count);
+        match self.order {
+            TextureDataOrder::LayerMajor => (0..layers).flat_map(|layer| (0..mips).map(move |mip| (layer, mip))).collect(),
+            TextureDataOrder::MipMajor => (0..mips).flat_map(|mip| (0..layers).map(move |layer| (layer, mip))).collect(),
+        }
+    }
+
+    // bytes of one layer of a mip level, in whole blocks
+    fn subresource_size(&self, mip: u32) -> usize {
+        let format = self.descriptor.format;
+        let (block_width, block_height) = format.block_dimensions();
+        let [width, height, depth] = self.descriptor.mip_size(mip);
+        let depth = if self.descriptor.view_dimension == TextureViewDimension::D3 { depth } else { 1 };
+        let blocks = width.div_ceil(block_width) * height.div_ceil(block_height) * depth;
+        blocks as usize * format.block_copy_size(None).unwrap_or(0) as usize
+    }
+
+    fn validated(mut self) -> Res<Self> {
+        let size: usize = self.subresources().into_iter().map(|(_, mip)| self.subresource_size(mip)).sum();
+        if self.data.len() < size {
+            bail!("texture data too short, expected {size} bytes, got {}", self.data.len());
+        }
+        self.data.truncate(size);
+        Ok(self)
+    }
+
+    pub fn required_features(&self) -> Features {
+        let format = self.descriptor.format;
+        let mut features = format.required_features();
+
+        if self.descriptor.view_dimension == TextureViewDimension::D3 {
+            if format.is_bcn() { features |= Features::TEXTURE_COMPRESSION_BC_SLICED_3D }
+            if format.is_astc() { features |= Features::TEXTURE_COMPRESSION_ASTC_SLICED_3D }
+        }
+        features
+    }
+
+    pub fn is_supported(&self, features: Features) -> bool {
+        features.contains(self.required_features())
+    }
+
+    // names of the required features missing from features, e.g. "TEXTURE_COMPRESSION_ETC2"
+    pub fn missing_features(&self, features: Features) -> String {
+        self.required_features().difference(features).iter_names().map(|(name, _)| name).collect::<Vec<_>>().join(" | ")
+    }
+
+    fn block_decoder(&self) -> Option<BlockDecoder> {
+        use TextureFormat::*;
+
+        Some(match self.descriptor.format {
+            Bc1RgbaUnorm | Bc1RgbaUnormSrgb => |block| decode_bc1(block, true),
+            Bc2RgbaUnorm | Bc2RgbaUnormSrgb => decode_bc2,
+            Bc3RgbaUnorm | Bc3RgbaUnormSrgb => decode_bc3,
+            Bc4RUnorm => decode_bc4,
+            Bc5RgUnorm => decode_bc5,
+            _ => return None,
+        })
+    }
+
+    // only BC1 to BC5 unorm formats, BC4/BC5 snorm, BC6H, BC7, ETC2, EAC and ASTC can't be decompressed
+    pub fn can_decompress(&self) -> bool { self.block_decoder().is_some() }
+
+    // decodes block compressed data to rgba8, see can_decompress
+    pub fn decompress(&self) -> Res<Self> {
+        use TextureFormat::*;
+
+        let format = self.descriptor.format;
+
+        let Some(decode) = self.block_decoder() else {
+            bail!("texture format {format:?} can't be decompressed on the cpu");
+        };
+
+        let mut descriptor = self.descriptor;
+        descriptor.format = if format.is_srgb() { Rgba8UnormSrgb } else { Rgba8Unorm };
+        descriptor.view_format = descriptor.format;
+
+        let mut data = Vec::new();
+        let mut blocks = self.data.chunks_exact(format.block_copy_size(None).unwrap_or(16) as usize);
+
+        for (_, mip) in self.subresources() {
+
+            let [width, height, depth] = self.descriptor.mip_size(mip).map(|v| v as usize);
+            let depth = if descriptor.view_dimension == TextureViewDimension::D3 { depth } else { 1 };
+
+            let mut pixels = vec![[0u8; 4]; width * height * depth];
+
+            for z in 0..depth {
+                for by in 0..height.div_ceil(4) {
+                    for bx in 0..width.div_ceil(4) {
+                        let texels = decode(blocks.next().context("texture data too short")?);
+
+                        for (i, texel) in texels.into_iter().enumerate() {
+                            let (x, y) = (bx * 4 + i % 4, by * 4 + i / 4);
+                            if x < width && y < height { pixels[(z * height + y) * width + x] = texel }
+                        }
+                    }
+                }
+            }
+
+            data.extend_from_slice(pixels.as_flattened());
+        }
+
+        Ok(Self { descriptor, order: self.order, data })
+    }
+
+    // decompresses BC1 to BC5 unorm formats on the cpu when the device doesn't support them,
+    // there is no cpu fallback for ETC2, EAC and ASTC (TEXTURE_COMPRESSION_ETC2 / TEXTURE_COMPRESSION_ASTC,
+    // unavailable on most desktop adapters) or BC6H and BC7, those fail with the missing feature names
+    pub fn upload(&self, gx: &impl WgxDeviceQueue, usage: TexUse) -> Res<TextureLot> {
+
+        if !self.is_supported(gx.device().features()) {
+            if !self.can_decompress() {
+                bail!(
+                    "texture format {:?} needs the device features {} and has no cpu fallback, transcode it to a supported format",
+                    self.descriptor.format, self.missing_features(gx.device().features()),
+                );
+            }
+            log::warn!("texture format {:?} not supported by the device, decompressing", self.descriptor.format);
+            return self.decompress()?.upload(gx, usage);
+        }
+
+        let mut descriptor = self.descriptor;
+        descriptor.usage = usage;
+
+        let texture = gx.device().create_texture_with_data(gx.queue(), &(&descriptor).into(), self.order, &self.data);
+        let view = texture.create_view(&descriptor.default_view());
+
+        Ok(TextureLot { texture, descriptor, view })
+    }
+}
+
+
+impl TextureLot {
+
+    // ktx2 or dds
+    pub fn from_texture_file_bytes(gx: &impl WgxDeviceQueue, bytes: &[u8], usage: TexUse) -> Res<Self> {
+        TextureFile::from_bytes(bytes)?.upload(gx, usage)
+    }
+
+    pub fn from_texture_file(gx: &impl WgxDeviceQueue, path: impl AsRef<Path>, usage: TexUse) -> Res<Self> {
+        TextureFile::load(path)?.upload(gx, usage)
+    }
+}
+
+
+// format mapping
+
+#[cfg(feature = "ktx2")]
+fn ktx2_format(format: ktx2::Format) -> Option<TextureFormat> {
+    use ktx2::Format as K;
+    use wgpu::{AstcBlock, AstcChannel};
+    use TextureFormat::*;
+
+    let astc = |block, channel| Some(Astc { block, channel });
+
+    match format {
+        K::R8_UNORM => Some(R8Unorm),
+        K::R8G8_UNORM => Some(Rg8Unorm),
+        K::R8G8B8A8_UNORM => Some(Rgba8Unorm),
+        K::R8G8B8A8_SRGB => Some(Rgba8UnormSrgb),
+        K::B8G8R8A8_UNORM => Some(Bgra8Unorm),
+        K::B8G8R8A8_SRGB => Some(Bgra8UnormSrgb),
+        K::A2B10G10R10_UNORM_PACK32 => Some(Rgb10a2Unorm),
+        K::B10G11R11_UFLOAT_PACK32 => Some(Rg11b10Ufloat),
+        K::E5B9G9R9_UFLOAT_PACK32 => Some(Rgb9e5Ufloat),
+        K::R16_SFLOAT => Some(R16Float),
+        K::R16G16_SFLOAT => Some(Rg16Float),
+        K::R16G16B16A16_SFLOAT => Some(Rgba16Float),
+        K::R32_SFLOAT => Some(R32Float),
+        K::R32G32B32A32_SFLOAT => Some(Rgba32Float),
+
+        K::BC1_RGB_UNORM_BLOCK | K::BC1_RGBA_UNORM_BLOCK => Some(Bc1RgbaUnorm),
+        K::BC1_RGB_SRGB_BLOCK | K::BC1_RGBA_SRGB_BLOCK => Some(Bc1RgbaUnormSrgb),
+        K::BC2_UNORM_BLOCK => Some(Bc2RgbaUnorm),
+        K::BC2_SRGB_BLOCK => Some(Bc2RgbaUnormSrgb),
+        K::BC3_UNORM_BLOCK => Some(Bc3RgbaUnorm),
+        K::BC3_SRGB_BLOCK => Some(Bc3RgbaUnormSrgb),
+        K::BC4_UNORM_BLOCK => Some(Bc4RUnorm),
+        K::BC4_SNORM_BLOCK => Some(Bc4RSnorm),
+        K::BC5_UNORM_BLOCK => Some(Bc5RgUnorm),
+        K::BC5_SNORM_BLOCK => Some(Bc5RgSnorm),
+        K::BC6H_UFLOAT_BLOCK => Some(Bc6hRgbUfloat),
+        K::BC6H_SFLOAT_BLOCK => Some(Bc6hRgbFloat),
+        K::BC7_UNORM_BLOCK => Some(Bc7RgbaUnorm),
+        K::BC7_SRGB_BLOCK => Some(Bc7RgbaUnormSrgb),
+
+        K::ETC2_R8G8B8_UNORM_BLOCK => Some(Etc2Rgb8Unorm),
+        K::ETC2_R8G8B8_SRGB_BLOCK => Some(Etc2Rgb8UnormSrgb),
+        K::ETC2_R8G8B8A1_UNORM_BLOCK => Some(Etc2Rgb8A1Unorm),
+        K::ETC2_R8G8B8A1_SRGB_BLOCK => Some(Etc2Rgb8A1UnormSrgb),
+        K::ETC2_R8G8B8A8_UNORM_BLOCK => Some(Etc2Rgba8Unorm),
+        K::ETC2_R8G8B8A8_SRGB_BLOCK => Some(Etc2Rgba8UnormSrgb),
+        K::EAC_R11_UNORM_BLOCK => Some(EacR11Unorm),
+        K::EAC_R11_SNORM_BLOCK => Some(EacR11Snorm),
+        K::EAC_R11G11_UNORM_BLOCK => Some(EacRg11Unorm),
+        K::EAC_R11G11_SNORM_BLOCK => Some(EacRg11Snorm),
+
+        K::ASTC_4x4_UNORM_BLOCK => astc(AstcBlock::B4x4, AstcChannel::Unorm),
+        K::ASTC_4x4_SRGB_BLOCK => astc(AstcBlock::B4x4, AstcChannel::UnormSrgb),
+        K::ASTC_4x4_SFLOAT_BLOCK => astc(AstcBlock::B4x4, AstcChannel::Hdr),
+        K::ASTC_5x4_UNORM_BLOCK => astc(AstcBlock::B5x4, AstcChannel::Unorm),
+        K::ASTC_5x4_SRGB_BLOCK => astc(AstcBlock::B5x4, AstcChannel::UnormSrgb),
+        K::ASTC_5x4_SFLOAT_BLOCK => astc(AstcBlock::B5x4, AstcChannel::Hdr),
+        K::ASTC_5x5_UNORM_BLOCK => astc(AstcBlock::B5x5, AstcChannel::Unorm),
+        K::ASTC_5x5_SRGB_BLOCK => astc(AstcBlock::B5x5, AstcChannel::UnormSrgb),
+        K::ASTC_5x5_SFLOAT_BLOCK => astc(AstcBlock::B5x5, AstcChannel::Hdr),
+        K::ASTC_6x5_UNORM_BLOCK => astc(AstcBlock::B6x5, AstcChannel::Unorm),
+        K::ASTC_6x5_SRGB_BLOCK => astc(AstcBlock::B6x5, AstcChannel::UnormSrgb),
+        K::ASTC_6x5_SFLOAT_BLOCK => astc(AstcBlock::B6x5, AstcChannel::Hdr),
+        K::ASTC_6x6_UNORM_BLOCK => astc(AstcBlock::B6x6, AstcChannel::Unorm),
+        K::ASTC_6x6_SRGB_BLOCK => astc(AstcBlock::B6x6, AstcChannel::UnormSrgb),
+        K::ASTC_6x6_SFLOAT_BLOCK => astc(AstcBlock::B6x6, AstcChannel::Hdr),
+        K::ASTC_8x5_UNORM_BLOCK => astc(AstcBlock::B8x5, AstcChannel::Unorm),
+        K::ASTC_8x5_SRGB_BLOCK => astc(AstcBlock::B8x5, AstcChannel::UnormSrgb),
+        K::ASTC_8x5_SFLOAT_BLOCK => astc(AstcBlock::B8x5, AstcChannel::Hdr),
+        K::ASTC_8x6_UNORM_BLOCK => astc(AstcBlock::B8x6, AstcChannel::Unorm),
+        K::ASTC_8x6_SRGB_BLOCK => astc(AstcBlock::B8x6, AstcChannel::UnormSrgb),
+        K::ASTC_8x6_SFLOAT_BLOCK => astc(AstcBlock::B8x6, AstcChannel::Hdr),
+        K::ASTC_8x8_UNORM_BLOCK => astc(AstcBlock::B8x8, AstcChannel::Unorm),
+        K::ASTC_8x8_SRGB_BLOCK => astc(AstcBlock::B8x8, AstcChannel::UnormSrgb),
+        K::ASTC_8x8_SFLOAT_BLOCK => astc(AstcBlock::B8x8, AstcChannel::Hdr),
+        K::ASTC_10x5_UNORM_BLOCK => astc(AstcBlock::B10x5, AstcChannel::Unorm),
+        K::ASTC_10x5_SRGB_BLOCK => astc(AstcBlock::B10x5, AstcChannel::UnormSrgb),
+        K::ASTC_10x5_SFLOAT_BLOCK => astc(AstcBlock::B10x5, AstcChannel::Hdr),
+        K::ASTC_10x6_UNORM_BLOCK => astc(AstcBlock::B10x6, AstcChannel::Unorm),
+        K::ASTC_10x6_SRGB_BLOCK => astc(AstcBlock::B10x6, AstcChannel::UnormSrgb),
+        K::ASTC_10x6_SFLOAT_BLOCK => astc(AstcBlock::B10x6, AstcChannel::Hdr),
+        K::ASTC_10x8_UNORM_BLOCK => astc(AstcBlock::B10x8, AstcChannel::Unorm),
+        K::ASTC_10x8_SRGB_BLOCK => astc(AstcBlock::B10x8, AstcChannel::UnormSrgb),
+        K::ASTC_10x8_SFLOAT_BLOCK => astc(AstcBlock::B10x8, AstcChannel::Hdr),
+        K::ASTC_10x10_UNORM_BLOCK => astc(AstcBlock::B10x10, AstcChannel::Unorm),
+        K::ASTC_10x10_SRGB_BLOCK => astc(AstcBlock::B10x10, AstcChannel::UnormSrgb),
+        K::ASTC_10x10_SFLOAT_BLOCK => astc(AstcBlock::B10x10, AstcChannel::Hdr),
+        K::ASTC_12x10_UNORM_BLOCK => astc(AstcBlock::B12x10, AstcChannel::Unorm),
+        K::ASTC_12x10_SRGB_BLOCK => astc(AstcBlock::B12x10, AstcChannel::UnormSrgb),
+        K::ASTC_12x10_SFLOAT_BLOCK => astc(AstcBlock::B12x10, AstcChannel::Hdr),
+        K::ASTC_12x12_UNORM_BLOCK => astc(AstcBlock::B12x12, AstcChannel::Unorm),
+        K::ASTC_12x12_SRGB_BLOCK => astc(AstcBlock::B12x12, AstcChannel::UnormSrgb),
+        K::ASTC_12x12_SFLOAT_BLOCK => astc(AstcBlock::B12x12, AstcChannel::Hdr),
+
+        _ => None,
+    }
+}
+
+#[cfg(feature = "dds")]
+fn dxgi_format(format: ddsfile::DxgiFormat) -> Option<TextureFormat> {
+    use ddsfile::DxgiFormat as D;
+    use TextureFormat::*;
+
+    match format {
+        D::R8_UNorm => Some(R8Unorm),
+        D::R8G8_UNorm => Some(Rg8Unorm),
+        D::R8G8B8A8_UNorm => Some(Rgba8Unorm),
+        D::R8G8B8A8_UNorm_sRGB => Some(Rgba8UnormSrgb),
+        D::B8G8R8A8_UNorm => Some(Bgra8Unorm),
+        D::B8G8R8A8_UNorm_sRGB => Some(Bgra8UnormSrgb),
+        D::R10G10B10A2_UNorm => Some(Rgb10a2Unorm),
+        D::R11G11B10_Float => Some(Rg11b10Ufloat),
+        D::R9G9B9E5_SharedExp => Some(Rgb9e5Ufloat),
+        D::R16_Float => Some(R16Float),
+        D::R16G16_Float => Some(Rg16Float),
+        D::R16G16B16A16_Float => Some(Rgba16Float),
+        D::R32_Float => Some(R32Float),
+        D::R32G32B32A32_Float => Some(Rgba32Float),
+
+        D::BC1_UNorm => Some(Bc1RgbaUnorm),
+        D::BC1_UNorm_sRGB => Some(Bc1RgbaUnormSrgb),
+        D::BC2_UNorm => Some(Bc2RgbaUnorm),
+        D::BC2_UNorm_sRGB => Some(Bc2RgbaUnormSrgb),
+        D::BC3_UNorm => Some(Bc3RgbaUnorm),
+        D::BC3_UNorm_sRGB => Some(Bc3RgbaUnormSrgb),
+        D::BC4_UNorm => Some(Bc4RUnorm),
+        D::BC4_SNorm => Some(Bc4RSnorm),
+        D::BC5_UNorm => Some(Bc5RgUnorm),
+        D::BC5_SNorm => Some(Bc5RgSnorm),
+        D::BC6H_UF16 => Some(Bc6hRgbUfloat),
+        D::BC6H_SF16 => Some(Bc6hRgbFloat),
+        D::BC7_UNorm => Some(Bc7RgbaUnorm),
+        D::BC7_UNorm_sRGB => Some(Bc7RgbaUnormSrgb),
+
+        _ => None,
+    }
+}
+
+#[cfg(feature = "dds")]
+fn d3d_format(format: ddsfile::D3DFormat) -> Option<TextureFormat> {
+    use ddsfile::D3DFormat as D;
+    use TextureFormat::*;
+
+    // names are in bit order, A8B8G8R8 is rgba in memory
+    match format {
+        D::A8B8G8R8 => Some(Rgba8Unorm),
+        D::A8R8G8B8 => Some(Bgra8Unorm),
+        D::L8 => Some(R8Unorm),
+        D::R16F => Some(R16Float),
+        D::A16B16G16R16F => Some(Rgba16Float),
+        D::R32F => Some(R32Float),
+        D::A32B32G32R32F => Some(Rgba32Float),
+        D::DXT1 => Some(Bc1RgbaUnorm),
+        D::DXT2 | D::DXT3 => Some(Bc2RgbaUnorm),
+        D::DXT4 | D::DXT5 => Some(Bc3RgbaUnorm),
+        _ => None,
+    }
+}
+
+
+// block decoding, 4x4 texels in row order
+
+type BlockDecoder = fn(&[u8]) -> [[u8; 4]; 16];
+
+fn rgb565(color: u16) -> [u8; 4] {
+    let (r, g, b) = ((color >> 11) as u8 & 31, (color >> 5) as u8 & 63, color as u8 & 31);
+    [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2, 255]
+}
+
+fn mix(a: [u8; 4], b: [u8; 4], wa: u16, wb: u16) -> [u8; 4] {
+    [0, 1, 2, 3].map(|i| ((a[i] as u16 * wa + b[i] as u16 * wb) / (wa + wb)) as u8)
+}
+
+// punch through alpha only applies to BC1
+fn decode_bc1(block: &[u8], punch_through: bool) -> [[u8; 4]; 16] {
+    let (c0, c1) = (u16::from_le_bytes([block[0], block[1]]), u16::from_le_bytes([block[2], block[3]]));
+    let (a, b) = (rgb565(c0), rgb565(c1));
+
+    let palette = if c0 > c1 || !punch_through {
+        [a, b, mix(a, b, 2, 1), mix(a, b, 1, 2)]
+    } else {
+        [a, b, mix(a, b, 1, 1), [0; 4]]
+    };
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    std::array::from_fn(|i| palette[(indices >> (2 * i) & 3) as usize])
+}
+
+// 8 bit channel with 3 bit indices
+fn decode_bc4_channel(block: &[u8]) -> [u8; 16] {
+    let (a, b) = (block[0] as u16, block[1] as u16);
+
+    let palette: [u8; 8] = if a > b {
+        std::array::from_fn(|i| match i { 0 => a, 1 => b, _ => (a * (8 - i as u16) + b * (i as u16 - 1)) / 7 } as u8)
+    } else {
+        std::array::from_fn(|i| match i { 0 => a, 1 => b, 6 => 0, 7 => 255, _ => (a * (6 - i as u16) + b * (i as u16 - 1)) / 5 } as u8)
+    };
+
+    let indices = u64::from_le_bytes([block[2], block[3], block[4], block[5], block[6], block[7], 0, 0]);
+    std::array::from_fn(|i| palette[(indices >> (3 * i) & 7) as usize])
+}
+
+fn decode_bc2(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut texels = decode_bc1(&block[8..], false);
+    for (i, texel) in texels.iter_mut().enumerate() {
+        let alpha = block[i / 2] >> (4 * (i % 2)) & 15;
+        texel[3] = alpha << 4 | alpha;
+    }
+    texels
+}
+
+fn decode_bc3(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut texels = decode_bc1(&block[8..], false);
+    for (texel, alpha) in texels.iter_mut().zip(decode_bc4_channel(block)) {
+        texel[3] = alpha;
+    }
+    texels
+}
+
+fn decode_bc4(block: &[u8]) -> [[u8; 4]; 16] {
+    decode_bc4_channel(block).map(|r| [r, 0, 0, 255])
+}
+
+fn decode_bc5(block: &[u8]) -> [[u8; 4]; 16] {
+    let (red, green) = (decode_bc4_channel(block), decode_bc4_channel(&block[8..]));
+    std::array::from_fn(|i| [red[i], green[i], 0, 255])
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn block_decoding() {
+        // red and blue endpoints, indices 0, 1, 2, 3 per row
+        let bc1 = [0x00, 0xF8, 0x1F, 0x00, 0xE4, 0xE4, 0xE4, 0xE4];
+        let texels = decode_bc1(&bc1, true);
+        assert_eq!(texels[..4], [[255, 0, 0, 255], [0, 0, 255, 255], [170, 0, 85, 255], [85, 0, 170, 255]]);
+
+        // punch through alpha with c0 <= c1
+        let bc1 = [0x1F, 0x00, 0x00, 0xF8, 0xFF, 0xFF, 0xFF, 0xFF];
+        assert_eq!(decode_bc1(&bc1, true)[0], [0; 4]);
+
+        // 8 interpolated values, index 2 is 6/7 a + 1/7 b
+        let bc4 = [255, 0, 0b010, 0, 0, 0, 0, 0];
+        assert_eq!(decode_bc4_channel(&bc4)[..2], [218, 255]);
+    }
+
+    #[test]
+    fn missing_features() {
+        let descriptor = TexDsc::new_2d([4, 4, 1], 1, TextureFormat::Etc2Rgb8Unorm, None, TexUse::TEXTURE_BINDING);
+        let file = TextureFile { descriptor, order: TextureDataOrder::LayerMajor, data: vec![0; 8] };
+
+        assert!(!file.can_decompress());
+        assert_eq!(file.missing_features(Features::empty()), "TEXTURE_COMPRESSION_ETC2");
+        assert_eq!(file.missing_features(Features::TEXTURE_COMPRESSION_ETC2), "");
+    }
+}
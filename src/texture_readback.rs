@@ -0,0 +1,190 @@
+use wgpu::{TextureAspect, TextureFormat, TexelCopyBufferLayout, COPY_BYTES_PER_ROW_ALIGNMENT};
+use crate::*;
+use anyhow::{Result as Res, bail};
+
+
+// region of a mip level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TexRegion {
+    pub origin: [u32; 3],
+    pub size: Option<[u32; 3]>, // up to the end of the mip level if None
+    pub aspect: TextureAspect,
+}
+
+impl TexRegion {
+    pub fn all() -> Self { Self::default() }
+    pub fn new(origin: [u32; 3], size: [u32; 3]) -> Self { Self { origin, size: Some(size), aspect: TextureAspect::All } }
+    pub fn aspect(mut self, aspect: TextureAspect) -> Self { self.aspect = aspect; self }
+}
+
+
+// layout of copied texels in a buffer, rows are padded to COPY_BYTES_PER_ROW_ALIGNMENT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadbackLayout {
+    pub size: [u32; 3],
+    pub bytes_per_row: u32,
+    pub padded_bytes_per_row: u32,
+    pub rows_per_image: u32, // rows of blocks for compressed formats
+}
+
+impl ReadbackLayout {
+
+    pub fn new(format: TextureFormat, aspect: TextureAspect, size: [u32; 3]) -> Res<Self> {
+
+        let Some(block_size) = format.block_copy_size(Some(aspect)) else {
+            bail!("texture format {format:?} with aspect {aspect:?} can't be copied");
+        };
+
+        let (block_width, block_height) = format.block_dimensions();
+        let bytes_per_row = size[0].div_ceil(block_width) * block_size;
+
+        Ok(Self {
+            size, bytes_per_row,
+            padded_bytes_per_row: bytes_per_row.next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT),
+            rows_per_image: size[1].div_ceil(block_height),
+        })
+    }
+
+    pub fn rows(&self) -> usize { self.rows_per_image as usize * self.size[2] as usize }
+
+    pub fn buffer_size(&self) -> u64 { self.padded_bytes_per_row as u64 * self.rows() as u64 }
+
+    pub fn buffer_layout(&self, offset: u64) -> TexelCopyBufferLayout {
+        TexelCopyBufferLayout {
+            offset,
+            bytes_per_row: Some(self.padded_bytes_per_row),
+            rows_per_image: Some(self.rows_per_image),
+        }
+    }
+
+    // strip the row padding
+    pub fn unpad(&self, padded: &[u8]) -> Vec<u8> {
+        let (row_size, padded_row_size) = (self.bytes_per_row as usize, self.padded_bytes_per_row as usize);
+
+        if row_size == padded_row_size {
+            return padded[..row_size * self.rows()].to_vec();
+        }
+
+        let mut data = Vec::with_capacity(row_size * self.rows());
+        for row in padded.chunks(padded_row_size).take(self.rows()) {
+            data.extend_from_slice(&row[..row_size]);
+        }
+        data
+    }
+}
+
+
+// pending readback, copy commands are recorded but the buffer is not mapped yet
+#[derive(Debug)]
+pub struct TextureReadback {
+    pub buffer: wgpu::Buffer,
+    pub layout: ReadbackLayout,
+}
+
+impl TextureReadback {
+
+    pub fn read_sync(&self, gx: &impl WgxDevice) -> Res<Vec<u8>> {
+        self.buffer.slice(..).with_map_sync(gx, MapMode::Read, |slice| {
+            self.layout.unpad(&slice.get_mapped_range())
+        })
+    }
+
+    // requests the mapping immediately, resolves when the device is polled after submission
+    pub fn read(self) -> impl Future<Output=Res<Vec<u8>>> {
+        let mapped = map_async(&self.buffer.slice(..), MapMode::Read);
+        async move {
+            mapped.await?;
+            let data = self.layout.unpad(&self.buffer.slice(..).get_mapped_range());
+            self.buffer.unmap();
+            Ok(data)
+        }
+    }
+}
+
+
+pub trait ReadPixels {
+
+    fn readback_texture(&self) -> &wgpu::Texture;
+
+    // checks the texture and resolves the region size
+    fn readback_layout(&self, region: TexRegion, mip: u32) -> Res<ReadbackLayout> {
+        let texture = self.readback_texture();
+        let format = texture.format();
+
+        if !texture.usage().contains(TexUse::COPY_SRC) {
+            bail!("texture needs COPY_SRC usage to be read back");
+        }
+        if texture.sample_count() > 1 {
+            bail!("multisampled textures can't be read back, read the resolve target");
+        }
+        if mip >= texture.mip_level_count() {
+            bail!("mip level {mip} out of range, texture has {} levels", texture.mip_level_count());
+        }
+
+        let size = region.size.unwrap_or_else(|| {
+            let [w, h, d] = texture.size().mip_level_size(mip, texture.dimension()).physical_size(format).to_arr();
+            let [x, y, z] = region.origin;
+            [w.saturating_sub(x), h.saturating_sub(y), d.saturating_sub(z)]
+        });
+
+        ReadbackLayout::new(format, region.aspect, size)
+    }
+
+    // record a copy of the region into the buffer at offset, laid out as the returned layout
+    fn copy_to_buffer(
+        &self, encoder: &mut wgpu::CommandEncoder, region: TexRegion, mip: u32, buffer: &wgpu::Buffer, offset: u64,
+    ) -> Res<ReadbackLayout> {
+        let layout = self.readback_layout(region, mip)?;
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: self.readback_texture(), mip_level: mip,
+                origin: ToOrigin3d::to(region.origin), aspect: region.aspect,
+            },
+            wgpu::TexelCopyBufferInfo { buffer, layout: layout.buffer_layout(offset) },
+            ToExtent3d::to(layout.size),
+        );
+
+        Ok(layout)
+    }
+
+    // record a copy of the region into a new buffer, the texture needs COPY_SRC usage
+    fn copy_to_readback(&self, gx: &impl WgxDevice, encoder: &mut wgpu::CommandEncoder, region: TexRegion, mip: u32)
+        -> Res<TextureReadback>
+    {
+        let layout = self.readback_layout(region, mip)?;
+        let buffer = gx.buffer(BufUse::MAP_READ | BufUse::COPY_DST, layout.buffer_size(), false);
+        self.copy_to_buffer(encoder, region, mip, &buffer, 0)?;
+        Ok(TextureReadback { buffer, layout })
+    }
+
+    fn submit_readback(&self, gx: &impl WgxDeviceQueue, region: TexRegion, mip: u32) -> Res<TextureReadback> {
+        let mut encoder = gx.command_encoder();
+        let readback = self.copy_to_readback(gx, &mut encoder, region, mip)?;
+        gx.queue().submit([encoder.finish()]);
+        Ok(readback)
+    }
+
+    // tightly packed texels, blocks for compressed formats
+    fn read_pixels(&self, gx: &impl WgxDeviceQueue, region: TexRegion, mip: u32) -> Res<Vec<u8>> {
+        self.submit_readback(gx, region, mip)?.read_sync(gx)
+    }
+
+    // on native the device has to be polled for the future to resolve
+    fn read_pixels_async(&self, gx: &impl WgxDeviceQueue, region: TexRegion, mip: u32) -> impl Future<Output=Res<Vec<u8>>> {
+        let mapped = self.submit_readback(gx, region, mip).map(TextureReadback::read);
+        async move { mapped?.await }
+    }
+}
+
+impl ReadPixels for wgpu::Texture {
+    fn readback_texture(&self) -> &wgpu::Texture { self }
+}
+
+impl ReadPixels for TextureLot {
+    fn readback_texture(&self) -> &wgpu::Texture { &self.texture }
+}
+
+impl ReadPixels for TextureTarget {
+    fn readback_texture(&self) -> &wgpu::Texture { &self.texture }
+}
@@ -0,0 +1,119 @@
+use std::{marker::PhantomData, ops::{Range, RangeBounds, Bound}};
+use wgpu::{Buffer, BufferAddress, BufferSize, BufferSlice, BufferBinding, BindingResource, COPY_BUFFER_ALIGNMENT};
+use crate::{*};
+use anyhow::{Result as Res, bail};
+
+
+// buffer of elements of T, all ranges and offsets are in elements
+
+#[derive(Debug, Clone)]
+pub struct TypedBuffer<T: AsBytes> {
+  pub buffer: Buffer,
+  len: usize,
+  _type: PhantomData<T>,
+}
+
+impl<T: AsBytes> TypedBuffer<T> {
+
+  pub fn new(gx: &impl WgxDevice, usage: BufUse, len: usize) -> Self {
+    Self::from_buffer(gx.buffer(usage, buffer_range::<T>(0..len).end, false))
+  }
+
+  pub fn from_data(gx: &impl WgxDevice, usage: BufUse, data: &[T]) -> Self {
+    Self::from_buffer(gx.buffer_from_data(usage, data))
+  }
+
+  // trailing bytes that don't fit an element are ignored
+  pub fn from_buffer(buffer: Buffer) -> Self {
+    let len = (buffer.size() / size_of::<T>() as u64) as usize;
+    Self { buffer, len, _type: PhantomData }
+  }
+
+  pub fn len(&self) -> usize { self.len }
+
+  pub fn is_empty(&self) -> bool { self.len == 0 }
+
+  pub fn usage(&self) -> BufUse { self.buffer.usage() }
+
+  pub fn byte_range(&self, range: impl RangeBounds<usize>) -> Res<Range<BufferAddress>> {
+    Ok(buffer_range::<T>(range.map_into(0..self.len)?))
+  }
+
+  // an unbounded end extends the range to the data length,
+  // returns the byte range which has to be aligned to COPY_BUFFER_ALIGNMENT
+  fn write_range(&self, range: impl RangeBounds<usize>, count: usize) -> Res<Range<BufferAddress>> {
+
+    let range = match range.end_bound() {
+      Bound::Unbounded => {
+        let start = match range.start_bound() {
+          Bound::Included(start) => *start,
+          Bound::Excluded(start) => start + 1,
+          Bound::Unbounded => 0,
+        };
+        (start..start + count).map_into(0..self.len)?
+      },
+      _ => range.map_into(0..self.len)?,
+    };
+
+    if range.len() != count {
+      bail!("data length {count} doesn't match range {range:?}");
+    }
+
+    let bytes = buffer_range::<T>(range.clone());
+
+    if !range.is_empty() && (!bytes.start.is_multiple_of(COPY_BUFFER_ALIGNMENT) || !(bytes.end - bytes.start).is_multiple_of(COPY_BUFFER_ALIGNMENT)) {
+      bail!("byte range {bytes:?} of {range:?} is not aligned to {COPY_BUFFER_ALIGNMENT} bytes");
+    }
+
+    Ok(bytes)
+  }
+
+  pub fn write(&self, gx: &impl WgxQueue, range: impl RangeBounds<usize>, data: &[T]) -> Res<()> {
+    let range = self.write_range(range, data.len())?;
+    gx.write_buffer(&self.buffer, range.start, data);
+    Ok(())
+  }
+
+  pub fn write_staged(&self, staging: &mut StagingEncoder, range: impl RangeBounds<usize>, data: &[T]) -> Res<()> {
+    let range = self.write_range(range, data.len())?;
+    if !range.is_empty() {
+      staging.write_data(&self.buffer, range.start, data);
+    }
+    Ok(())
+  }
+
+  // returns the number of written elements, Err if the iterator doesn't fit,
+  // iterators without an exact size hint are collected first
+  pub fn write_iter_staged<I: Iterator<Item=T>>(&self, staging: &mut StagingEncoder, offset: usize, data: I) -> Res<usize> {
+
+    let (count, upper) = data.size_hint();
+
+    if upper != Some(count) {
+      let data: Vec<T> = data.collect();
+      self.write_staged(staging, offset.., &data)?;
+      return Ok(data.len());
+    }
+
+    let range = self.write_range(offset.., count)?;
+    if range.is_empty() { return Ok(0) }
+
+    match staging.write_iter(&self.buffer, range.start, data) {
+      Ok(count) => Ok(count),
+      Err(count) => bail!("data exceeds the buffer after {count} elements"),
+    }
+  }
+
+  pub fn slice(&self, range: impl RangeBounds<usize>) -> Res<BufferSlice<'_>> {
+    Ok(self.buffer.slice(self.byte_range(range)?))
+  }
+
+  pub fn binding(&self, range: impl RangeBounds<usize>) -> Res<BufferBinding<'_>> {
+    let range = self.byte_range(range)?;
+    if range.is_empty() { bail!("binding range can't be empty") }
+    Ok(BufferBinding { buffer: &self.buffer, offset: range.start, size: BufferSize::new(range.end - range.start) })
+  }
+
+  pub fn as_entire_binding(&self) -> BindingResource<'_> {
+    self.buffer.as_entire_binding()
+  }
+}
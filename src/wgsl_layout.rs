@@ -0,0 +1,198 @@
+use crate::*;
+
+
+// alignment and size of host-shareable types in WGSL memory layout, derive it for #[repr(C)] structs,
+// the storage address space layout is similar to std430, the uniform one adds std140-like rules
+pub trait WgslLayout: AsBytes {
+
+    const ALIGN: u64;
+    const SIZE: u64;
+
+    // required alignment and space as member in the uniform address space
+    const UNIFORM_ALIGN: u64 = Self::ALIGN;
+    const UNIFORM_SIZE: u64 = Self::SIZE;
+    const UNIFORM: bool = true; // if usable in the uniform address space
+
+    // for binding! entries
+    const MIN_BINDING_SIZE: u64 = Self::SIZE;
+}
+
+
+// explicit padding, has no WGSL counterpart and doesn't affect the alignment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Pad<const N: usize>([u8; N]);
+
+impl<const N: usize> Pad<N> {
+    pub const fn new() -> Self { Self([0; N]) }
+}
+
+impl<const N: usize> Default for Pad<N> { fn default() -> Self { Self::new() } }
+
+// SAFETY: a transparent byte array
+unsafe impl<const N: usize> Zeroable for Pad<N> {}
+unsafe impl<const N: usize> Pod for Pad<N> {}
+
+impl<const N: usize> WgslLayout for Pad<N> {
+    const ALIGN: u64 = 1;
+    const SIZE: u64 = N as u64;
+}
+
+
+// helper macro
+macro_rules! impl_wgsl_layout {
+    ($align:literal, $size:literal => $($type:ty),+) => {
+        $( impl WgslLayout for $type { const ALIGN: u64 = $align; const SIZE: u64 = $size; } )+
+    };
+}
+
+impl_wgsl_layout!(4, 4 => f32, i32, u32);
+impl_wgsl_layout!(16, 16 => Color);
+
+#[cfg(feature = "math")]
+mod math_impl {
+    use super::*;
+    use crate::math::*;
+
+    impl_wgsl_layout!(8, 8 => Vec2, IVec2, UVec2);
+    impl_wgsl_layout!(16, 12 => Vec3, IVec3, UVec3);
+    impl_wgsl_layout!(16, 16 => Vec4, IVec4, UVec4, Quat, Vec3P);
+    impl_wgsl_layout!(8, 16 => Mat2);
+    impl_wgsl_layout!(16, 48 => Mat3P);
+    impl_wgsl_layout!(16, 64 => Mat4);
+}
+
+impl<T: WgslLayout, const N: usize> WgslLayout for [T; N] where [T; N]: AsBytes {
+    const ALIGN: u64 = T::ALIGN;
+    const SIZE: u64 = {
+        assert!(N > 0, "WGSL arrays need at least one element");
+        assert!(T::SIZE.is_multiple_of(T::ALIGN), "array element size has to be a multiple of its WGSL alignment, use padded types like Vec3P");
+        T::SIZE * N as u64
+    };
+    const UNIFORM_ALIGN: u64 = T::ALIGN.next_multiple_of(16);
+    const UNIFORM: bool = T::UNIFORM && T::SIZE.is_multiple_of(16);
+}
+
+
+// used by the derive
+
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct WgslField {
+    pub offset: u64, // in the rust struct
+    pub align: u64,
+    pub size: u64,
+    pub uniform_align: u64,
+    pub uniform_size: u64,
+    pub uniform: bool,
+}
+
+impl WgslField {
+    pub const fn of<T: WgslLayout>(offset: u64) -> Self {
+        assert!(T::SIZE == size_of::<T>() as u64, "WgslLayout::SIZE of a field type doesn't match its size");
+        Self {
+            offset, align: T::ALIGN, size: T::SIZE,
+            uniform_align: T::UNIFORM_ALIGN, uniform_size: T::UNIFORM_SIZE, uniform: T::UNIFORM,
+        }
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct WgslStruct<const N: usize> {
+    pub align: u64,
+    pub size: u64,
+    pub offsets: [u64; N], // WGSL member offsets
+    pub uniform: [bool; N],
+}
+
+impl<const N: usize> WgslStruct<N> {
+
+    pub const fn new(fields: [WgslField; N]) -> Self {
+
+        let mut layout = Self { align: 1, size: 0, offsets: [0; N], uniform: [true; N] };
+        let (mut end, mut uniform_end) = (0u64, 0);
+        let mut i = 0;
+
+        while i < N {
+            let field = fields[i];
+            let offset = end.next_multiple_of(field.align);
+
+            layout.offsets[i] = offset;
+            // only Pad has an alignment of 1, it may fill the space claimed by struct members
+            layout.uniform[i] = field.uniform && offset.is_multiple_of(field.uniform_align) && (
+                field.align == 1 || offset >= uniform_end
+            );
+            if field.align > layout.align { layout.align = field.align }
+
+            end = offset + field.size;
+            if offset + field.uniform_size > uniform_end { uniform_end = offset + field.uniform_size }
+            i += 1;
+        }
+
+        layout.size = end.next_multiple_of(layout.align);
+        layout
+    }
+
+    pub const fn is_uniform(&self) -> bool {
+        let mut i = 0;
+        while i < N {
+            if !self.uniform[i] { return false }
+            i += 1;
+        }
+        true
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Copy, Pod, Zeroable, WgslLayout)]
+    #[repr(C)]
+    #[wgsl(crate = crate)]
+    struct Light {
+        position: Color,
+        intensity: f32,
+        _pad: Pad<12>,
+    }
+
+    #[derive(Clone, Copy, Pod, Zeroable, WgslLayout)]
+    #[repr(C)]
+    #[wgsl(uniform, crate = crate)]
+    struct Uniforms {
+        color: Color,
+        scale: f32,
+        flags: u32,
+        _pad: Pad<8>,
+        lights: [Light; 2],
+    }
+
+    #[derive(Clone, Copy, Pod, Zeroable, WgslLayout)]
+    #[repr(C)]
+    #[wgsl(crate = crate)]
+    struct Single(u32);
+
+    #[derive(Clone, Copy, Pod, Zeroable, WgslLayout)]
+    #[repr(C)]
+    #[wgsl(crate = crate)]
+    struct Storage(u32, [f32; 3]);
+
+    #[test]
+    fn derived_layouts() {
+        assert_eq!((Light::ALIGN, Light::SIZE, Light::UNIFORM), (16, 32, true));
+        assert_eq!((Uniforms::ALIGN, Uniforms::SIZE, Uniforms::MIN_BINDING_SIZE), (16, 96, 96));
+        assert_eq!((Storage::ALIGN, Storage::SIZE, Storage::UNIFORM), (4, 16, false));
+
+        let layout = WgslStruct::new([WgslField::of::<f32>(0), WgslField::of::<Color>(4), WgslField::of::<f32>(20)]);
+        assert_eq!((layout.offsets, layout.size, layout.align), ([0, 16, 32], 48, 16));
+
+        // struct members claim 16 bytes aligned space in uniforms
+        let layout = WgslStruct::new([WgslField::of::<Single>(0), WgslField::of::<f32>(4)]);
+        assert_eq!((layout.offsets, layout.uniform), ([0, 4], [true, false]));
+
+        let layout = WgslStruct::new([WgslField::of::<Single>(0), WgslField::of::<Pad<12>>(4), WgslField::of::<f32>(16)]);
+        assert_eq!((layout.offsets, layout.uniform), ([0, 4, 16], [true, true, true]));
+    }
+}
@@ -12,9 +12,15 @@ pub struct Wgx {
     pub queue: wgpu::Queue,
     pub instance: wgpu::Instance,
     pub adapter: wgpu::Adapter,
+    pub errors: DeviceErrors,
 }
 
 impl Wgx {
+    pub fn from_parts(instance: wgpu::Instance, adapter: wgpu::Adapter, device: wgpu::Device, queue: wgpu::Queue) -> Self {
+        let errors = DeviceErrors::install(&device);
+        Self { device, queue, instance, adapter, errors }
+    }
+
     pub fn instance() -> wgpu::Instance {
         wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle_from_env())
     }
@@ -38,6 +44,21 @@ impl Wgx {
         Ok((adapter, surface))
     }
 
+    pub async fn select_adapter<W: Into<wgpu::SurfaceTarget<'static>>>(
+        instance: &wgpu::Instance, window: Option<W>, selector: &AdapterSelector,
+    )
+        -> Res<(wgpu::Adapter, Option<wgpu::Surface<'static>>)>
+    {
+        let surface = if let Some(win) = window {
+            Some(instance.create_surface(win)?)
+        }
+        else { None };
+
+        let adapter = selector.select(instance, surface.as_ref()).await?;
+
+        Ok((adapter, surface))
+    }
+
     pub async fn request_device(adapter: &wgpu::Adapter, features:wgpu::Features, limits:wgpu::Limits) -> Res<(wgpu::Device, wgpu::Queue)> {
 
         let adapter_limits = adapter.limits();
@@ -78,7 +99,18 @@ impl Wgx {
         let instance = Self::instance();
         let (adapter, surface) = Self::request_adapter(&instance, window).await?;
         let (device, queue) = Self::request_device(&adapter, features, limits).await?;
-        Ok((Self {device, queue, instance, adapter}, surface))
+        Ok((Self::from_parts(instance, adapter, device, queue), surface))
+    }
+
+    pub async fn new_with_selector<W: Into<wgpu::SurfaceTarget<'static>>>(
+        window:Option<W>, selector:&AdapterSelector, features:wgpu::Features, limits:wgpu::Limits
+    )
+        -> Res<(Self, Option<wgpu::Surface<'static>>)>
+    {
+        let instance = Self::instance();
+        let (adapter, surface) = Self::select_adapter(&instance, window, selector).await?;
+        let (device, queue) = Self::request_device(&adapter, features, limits).await?;
+        Ok((Self::from_parts(instance, adapter, device, queue), surface))
     }
 
     pub async fn new_with_target<W: Into<wgpu::SurfaceTarget<'static>>>(
@@ -90,6 +122,57 @@ impl Wgx {
         let target = SurfaceTarget::new_with_default_config(&gx, surface.unwrap(), window_size, srgb, msaa, depth_testing);
         Ok((gx, target))
     }
+
+    pub fn backend(&self) -> wgpu::Backend { self.adapter.get_info().backend }
+}
+
+
+// headless
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadlessOptions {
+    pub power_preference: wgpu::PowerPreference,
+    pub fallback: bool, // use a fallback (software) adapter if no hardware adapter is available
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            power_preference: wgpu::PowerPreference::from_env().unwrap_or(wgpu::PowerPreference::HighPerformance),
+            fallback: true,
+        }
+    }
+}
+
+impl Wgx {
+    pub async fn request_headless_adapter(instance: &wgpu::Instance, options: HeadlessOptions) -> Res<wgpu::Adapter> {
+
+        let request = |force_fallback_adapter| instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: options.power_preference,
+            force_fallback_adapter,
+            compatible_surface: None,
+        });
+
+        let adapter = match request(false).await {
+            Ok(adapter) => adapter,
+            Err(err) if options.fallback => {
+                log::warn!("no hardware adapter: {err}, trying fallback adapter");
+                request(true).await.context("couldn't get hardware or fallback adapter")?
+            },
+            Err(err) => return Err(err).context("couldn't get adapter"),
+        };
+
+        let info = adapter.get_info();
+        log::info!("headless adapter: {} ({:?}, {:?})", info.name, info.backend, info.device_type);
+
+        Ok(adapter)
+    }
+
+    pub async fn headless(features:wgpu::Features, limits:wgpu::Limits, options:HeadlessOptions) -> Res<Self> {
+        let instance = Self::instance();
+        let adapter = Self::request_headless_adapter(&instance, options).await?;
+        let (device, queue) = Self::request_device(&adapter, features, limits).await?;
+        Ok(Self::from_parts(instance, adapter, device, queue))
+    }
 }
 
 
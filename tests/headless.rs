@@ -0,0 +1,42 @@
+use wgx::*;
+
+
+#[test]
+fn headless_clear_and_readback() {
+
+    let options = HeadlessOptions { fallback: true, ..HeadlessOptions::default() };
+    let gx = pollster::block_on(Wgx::headless(features!(), limits!{}, options)).unwrap();
+
+    let target = TextureTarget::new(&gx, [4, 4], 1, None, TexFmt::Rgba8Unorm, None, TexUse::COPY_SRC);
+
+    gx.with_encoder(|encoder| {
+        encoder.with_render_pass(target.attachments(Some(Color::RED), None, None), |_| {});
+    });
+
+    let pixels = target.read_pixels(&gx, TexRegion::all(), 0).unwrap();
+
+    assert_eq!(pixels.len(), 4 * 4 * 4);
+    assert!(pixels.chunks(4).all(|pixel| pixel == [255, 0, 0, 255]));
+}
+
+
+#[test]
+fn headless_recreate_device() {
+
+    let mut gx = pollster::block_on(Wgx::headless(features!(), limits!{}, HeadlessOptions::default())).unwrap();
+    let mut target = TextureTarget::new(&gx, [4, 4], 1, None, TexFmt::Rgba8Unorm, None, TexUse::COPY_SRC);
+
+    let mut report = pollster::block_on(gx.recreate_device(features!(), limits!{}, &AdapterSelector::new(), None)).unwrap();
+    report.recreate(&gx, &mut target);
+
+    assert!(!report.adapter_changed);
+    assert_eq!(report.recreated, [std::any::type_name::<TextureTarget>()]);
+
+    gx.with_encoder(|encoder| {
+        encoder.with_render_pass(target.attachments(Some(Color::BLUE), None, None), |_| {});
+    });
+
+    let pixels = target.read_pixels(&gx, TexRegion::all(), 0).unwrap();
+    assert!(pixels.chunks(4).all(|pixel| pixel == [0, 0, 255, 255]));
+    assert!(gx.errors.check().is_ok());
+}
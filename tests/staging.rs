@@ -0,0 +1,48 @@
+use wgx::*;
+
+
+fn headless() -> Wgx {
+    pollster::block_on(Wgx::headless(features!(), limits!{}, HeadlessOptions::default())).unwrap()
+}
+
+
+#[test]
+fn staging_write_data_at_offset() {
+
+    let gx = headless();
+    let buffer = gx.buffer(BufUse::MAP_READ | BufUse::COPY_DST, 16, false);
+
+    let mut encoder = StagingEncoder::new(&gx, 64);
+    encoder.write_data(&buffer, 0, &[1u32, 2]);
+    encoder.write_data(&buffer, 8, &[3u32, 4]);
+    encoder.submit(&gx);
+
+    let bytes = buffer.slice(..).with_map_sync(&gx, MapMode::Read, |slice| slice.get_mapped_range().to_vec()).unwrap();
+
+    assert_eq!(bytemuck::cast_slice::<u8, u32>(&bytes), &[1, 2, 3, 4]);
+}
+
+
+#[test]
+fn typed_buffer_staged_writes() {
+
+    let gx = headless();
+    let buffer = TypedBuffer::<u16>::new(&gx, BufUse::MAP_READ | BufUse::COPY_DST, 8);
+
+    let mut encoder = StagingEncoder::new(&gx, 64);
+
+    assert_eq!(buffer.write_iter_staged(&mut encoder, 0, [1u16, 2, 3, 4].into_iter()).unwrap(), 4);
+    assert_eq!(buffer.write_iter_staged(&mut encoder, 4, (5u16..9).filter(|_| true)).unwrap(), 4);
+
+    // empty, too long and misaligned writes
+    assert_eq!(buffer.write_iter_staged(&mut encoder, 8, std::iter::empty()).unwrap(), 0);
+    assert!(buffer.write_iter_staged(&mut encoder, 6, 0u16..4).is_err());
+    assert!(buffer.write_staged(&mut encoder, 1..3, &[0, 0]).is_err());
+    assert!(buffer.write_staged(&mut encoder, 2..3, &[0]).is_err());
+
+    encoder.submit(&gx);
+
+    let bytes = buffer.buffer.slice(..).with_map_sync(&gx, MapMode::Read, |slice| slice.get_mapped_range().to_vec()).unwrap();
+
+    assert_eq!(bytemuck::cast_slice::<u8, u16>(&bytes), &[1, 2, 3, 4, 5, 6, 7, 8]);
+}
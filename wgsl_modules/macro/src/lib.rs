@@ -1,7 +1,7 @@
 #![feature(proc_macro_tracked_path)]
 
 use std::{cell::RefCell, path::{Path, PathBuf}};
-use wgsl_modules_loader::{Module, ModuleCache, naga::valid::{ValidationFlags, Capabilities}};
+use wgsl_modules_loader::{Module, ModuleCache, naga::{self, valid::{ValidationFlags, Capabilities, ModuleInfo}}};
 
 use proc_macro::{TokenStream, TokenTree, Literal, Span, tracked};
 use syn::{parse_macro_input, LitStr};
@@ -13,14 +13,17 @@ use anyhow::{Result as Res};
 thread_local!(static CACHE: RefCell<ModuleCache> = ModuleCache::new().into());
 
 
+mod types;
+
+
 // helper
-fn handle_result(res: Res<&Module>, path: &Path) -> TokenStream {
+fn handle_result(res: Res<&Module>, path: &Path, output: impl FnOnce(&Module, &naga::Module, &ModuleInfo) -> TokenStream) -> TokenStream {
     match res.and_then(|module| {
         // validate naga_module
-        module.naga_module(Some((ValidationFlags::all(), Capabilities::all())))?;
-        Ok(module)
+        let (naga_module, info) = module.naga_module(Some((ValidationFlags::all(), Capabilities::all())))?;
+        Ok((module, naga_module, info.unwrap()))
     }) {
-        Ok(module) => {
+        Ok((module, naga_module, info)) => {
             // track source code files
             if path.exists() {
                 tracked::path(path.to_str().unwrap());
@@ -33,15 +36,20 @@ fn handle_result(res: Res<&Module>, path: &Path) -> TokenStream {
                 }
             }
 
-            TokenTree::from(Literal::string(module.code())).into()
+            output(module, &naga_module, &info)
         },
         Err(err) => {
             let err = format!("{err:?}");
-            quote!(compile_error!(#err)).into()
+            // braces work in expression and item position
+            quote!(compile_error!{#err}).into()
         },
     }
 }
 
+fn code_literal(module: &Module, _: &naga::Module, _: &ModuleInfo) -> TokenStream {
+    TokenTree::from(Literal::string(module.code())).into()
+}
+
 
 
 #[proc_macro]
@@ -51,14 +59,14 @@ pub fn include(input: TokenStream) -> TokenStream {
     let path = dir_path.join(parse_macro_input!(input as LitStr).value());
 
     CACHE.with_borrow_mut(|cache| {
-        handle_result(cache.load_from_path(&path), &path)
+        handle_result(cache.load_from_path(&path), &path, code_literal)
     })
 }
 
 
 
 use quote::quote_spanned;
-use syn::{token::Le, parse::{self, ParseBuffer, Error}};
+use syn::{Token, token::Le, parse::{self, ParseBuffer, Error}};
 use proc_macro::{Delimiter};
 
 
@@ -120,6 +128,43 @@ pub fn inline(input: TokenStream) -> TokenStream {
     }
 
     CACHE.with_borrow_mut(|cache| {
-        handle_result(cache.load(&path, source), &path)
+        handle_result(cache.load(&path, source), &path, code_literal)
     })
-}
\ No newline at end of file
+}
+
+
+// used by include_types!, expects the path to wgsl_modules and the module path
+#[doc(hidden)]
+#[proc_macro]
+pub fn __include_types(input: TokenStream) -> TokenStream {
+
+    let mut input = input.into_iter();
+    let mut span = Span::call_site();
+
+    let krate = proc_macro2::TokenStream::from(TokenStream::from(next!(span, input)));
+
+    let comma_token = next!(span, input).into();
+    parse_macro_input!(comma_token as Token![,]);
+
+    // resolve relative to the file of the path literal, it may be wrapped by the declarative macro
+    let path_token = match next!(span, input) {
+        TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+            let mut inner = group.stream().into_iter();
+            next!(span, inner)
+        },
+        token => token,
+    };
+    let dir_path = PathBuf::from(path_token.span().file()).parent().unwrap().to_owned();
+    let path_token = path_token.into();
+    let path = dir_path.join(parse_macro_input!(path_token as LitStr).value());
+
+    if let Some(token) = input.next() {
+        return quote_spanned!{token.span().into()=>compile_error!("unexpected token")}.into()
+    }
+
+    CACHE.with_borrow_mut(|cache| {
+        handle_result(cache.load_from_path(&path), &path, |module, naga_module, info| {
+            types::generate(&krate, module, naga_module, info).into()
+        })
+    })
+}
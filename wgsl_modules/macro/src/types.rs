@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use wgsl_modules_loader::{Module, naga::{self, Handle, Type, TypeInner, ScalarKind, Scalar, ArraySize, valid::{ModuleInfo, TypeFlags}}};
+use proc_macro2::{TokenStream, Ident, Literal, Span};
+use quote::{quote, format_ident};
+
+
+// rust identifier, raw if it's a keyword
+fn ident(name: &str) -> Ident {
+    syn::parse_str::<Ident>(name).unwrap_or_else(|_| Ident::new_raw(name, Span::call_site()))
+}
+
+// camelCase or snake_case to UPPER_SNAKE_CASE
+fn const_ident(name: &str) -> Ident {
+    let mut upper = String::with_capacity(name.len() + 4);
+    let mut prev_lower = false;
+
+    for c in name.chars() {
+        if c.is_uppercase() && prev_lower { upper.push('_') }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        upper.extend(c.to_uppercase());
+    }
+
+    ident(&upper)
+}
+
+fn len(n: u32) -> Literal { Literal::usize_unsuffixed(n as usize) }
+
+
+// rust type and size of host-shareable scalars
+fn scalar_type(Scalar { kind, width }: Scalar) -> Result<(TokenStream, u32), String> {
+    let ty = match (kind, width) {
+        (ScalarKind::Float, 4) => quote!(f32),
+        (ScalarKind::Float, 8) => quote!(f64),
+        (ScalarKind::Sint, 4) => quote!(i32),
+        (ScalarKind::Sint, 8) => quote!(i64),
+        (ScalarKind::Uint, 4) => quote!(u32),
+        (ScalarKind::Uint, 8) => quote!(u64),
+        _ => return Err(format!("{kind:?} scalars of {width} bytes have no rust type")), // f16 and bool
+    };
+    Ok((ty, width as u32))
+}
+
+
+struct Generator<'a> {
+    module: &'a naga::Module,
+    info: &'a ModuleInfo,
+    krate: &'a TokenStream,
+    structs: HashMap<Handle<Type>, (Ident, u32)>,
+}
+
+impl Generator<'_> {
+
+    // rust type and size, the error describes why the type can't be represented
+    fn rust_type(&self, ty: Handle<Type>) -> Result<(TokenStream, u32), String> {
+        match self.module.types[ty].inner {
+
+            TypeInner::Scalar(scalar) | TypeInner::Atomic(scalar) => scalar_type(scalar),
+
+            TypeInner::Vector { size, scalar } => {
+                let ((ty, width), n) = (scalar_type(scalar)?, size as u32);
+                let n_lit = len(n);
+                Ok((quote!([#ty; #n_lit]), width * n))
+            },
+
+            // columns of 3 rows are aligned like vec4
+            TypeInner::Matrix { columns, rows, scalar } => {
+                let (ty, width) = scalar_type(scalar)?;
+                let (columns, rows) = (columns as u32, if rows as u32 == 3 { 4 } else { rows as u32 });
+                let (c_lit, r_lit) = (len(columns), len(rows));
+                Ok((quote!([[#ty; #r_lit]; #c_lit]), width * rows * columns))
+            },
+
+            TypeInner::Array { base, size: ArraySize::Constant(count), stride } => {
+                let (mut elem, mut size) = self.rust_type(base)?;
+
+                // vec3 elements are padded to the stride
+                if let TypeInner::Vector { scalar, .. } = self.module.types[base].inner && size < stride {
+                    let (ty, width) = scalar_type(scalar)?;
+                    (elem, size) = (quote!([#ty; 4]), width * 4);
+                }
+
+                if size != stride {
+                    return Err(format!("array stride {stride} doesn't match the element size {size}"));
+                }
+
+                let count_lit = len(count.get());
+                Ok((quote!([#elem; #count_lit]), stride * count.get()))
+            },
+
+            TypeInner::Array { .. } => Err("runtime sized arrays are only supported as last struct member".to_string()),
+
+            TypeInner::Struct { .. } => self.structs.get(&ty).map(|(ident, size)| (quote!(#ident), *size)).ok_or_else(||
+                format!("struct `{}` wasn't generated", self.module.types[ty].name.as_deref().unwrap_or("?"))
+            ),
+
+            ref inner => Err(format!("{inner:?} is not host-shareable")),
+        }
+    }
+
+    // padding is inserted explicitly, structs with a runtime sized array cover the fixed part only,
+    // structs that aren't host-shareable (e.g. with bool members) are skipped, host-shareable structs
+    // that can't be represented fail to compile
+    fn generate_struct(&mut self, handle: Handle<Type>, name: &str) -> Option<TokenStream> {
+
+        let module = self.module;
+        let TypeInner::Struct { members, span } = &module.types[handle].inner else { return None };
+
+        if !self.info[handle].contains(TypeFlags::HOST_SHAREABLE) { return None }
+
+        let mut fields = Vec::with_capacity(members.len());
+        let (mut offset, mut size) = (0, *span);
+
+        for (i, member) in members.iter().enumerate() {
+
+            if let TypeInner::Array { size: ArraySize::Dynamic, .. } = module.types[member.ty].inner {
+                size = member.offset;
+                break;
+            }
+
+            let (ty, member_size) = match self.rust_type(member.ty) {
+                Ok(ty) => ty,
+                Err(reason) => {
+                    let member = member.name.clone().unwrap_or_else(|| i.to_string());
+                    let msg = format!("can't generate struct `{name}`, member `{member}`: {reason}");
+                    return Some(quote!(compile_error!{#msg}));
+                },
+            };
+
+            if member.offset > offset {
+                let (pad, n) = (format_ident!("_pad{i}"), len(member.offset - offset));
+                fields.push(quote!(pub #pad: [u8; #n]));
+            }
+
+            let field = member.name.as_deref().map_or_else(|| format_ident!("member{i}"), ident);
+            fields.push(quote!(pub #field: #ty));
+
+            offset = member.offset + member_size;
+        }
+
+        if size > offset {
+            let n = len(size - offset);
+            fields.push(quote!(pub _pad: [u8; #n]));
+        }
+
+        let (krate, ident, size_lit) = (self.krate, ident(name), len(size));
+        let size_msg = format!("size of `{name}` doesn't match its WGSL layout");
+
+        let tokens = quote!{
+            #[repr(C)]
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub struct #ident { #(#fields),* }
+
+            // SAFETY: all padding is explicit, the size assertion guarantees there is no implicit padding
+            unsafe impl #krate::bytemuck::Zeroable for #ident {}
+            unsafe impl #krate::bytemuck::Pod for #ident {}
+
+            const _: () = assert!(::core::mem::size_of::<#ident>() == #size_lit, #size_msg);
+        };
+
+        self.structs.insert(handle, (ident, size));
+        Some(tokens)
+    }
+}
+
+
+pub fn generate(krate: &TokenStream, module: &Module, naga_module: &naga::Module, info: &ModuleInfo) -> TokenStream {
+
+    let mut generator = Generator { module: naga_module, info, krate, structs: HashMap::new() };
+
+    // types are ordered by dependency
+    let structs: Vec<_> = naga_module.types.iter().filter_map(|(handle, ty)| {
+        let name = ty.name.as_deref().filter(|name| !name.starts_with("__"))?;
+        generator.generate_struct(handle, name)
+    }).collect();
+
+    let bindings = naga_module.global_variables.iter().filter_map(|(_, var)| {
+        let (name, binding) = (var.name.as_deref()?, var.binding.as_ref()?);
+        let (group, binding) = (binding.group, binding.binding);
+        let (group_ident, binding_ident) = (const_ident(&format!("{name}_group")), const_ident(&format!("{name}_binding")));
+        Some(quote!{
+            pub const #group_ident: u32 = #group;
+            pub const #binding_ident: u32 = #binding;
+        })
+    });
+
+    let entry_points = naga_module.entry_points.iter().map(|entry| {
+        let (ident, name) = (const_ident(&entry.name), &entry.name);
+        quote!(pub const #ident: &str = #name;)
+    });
+
+    // pipeline constants are keyed by id if given
+    let overrides = naga_module.overrides.iter().filter_map(|(_, item)| {
+        let name = item.name.as_deref()?;
+        let (ident, key) = (const_ident(name), item.id.map_or_else(|| name.to_string(), |id| id.to_string()));
+        Some(quote!(pub const #ident: &str = #key;))
+    });
+
+    let source = Literal::string(module.code());
+
+    quote!{
+        pub const SOURCE: &str = #source;
+
+        #(#structs)*
+
+        pub mod bindings { #(#bindings)* }
+
+        pub mod entry_points { #(#entry_points)* }
+
+        pub mod overrides { #(#overrides)* }
+    }
+}
@@ -1,5 +1,16 @@
-
 pub use wgsl_modules_macro::*;
 
 #[cfg(feature = "loader")]
-pub use wgsl_modules_loader::*;
\ No newline at end of file
+pub use wgsl_modules_loader::*;
+
+#[doc(hidden)]
+pub use bytemuck;
+
+
+// generates #[repr(C)] Pod structs with explicit padding for host-shareable WGSL structs, SOURCE with
+// the code and constants in the modules bindings, entry_points and overrides,
+// fails to compile if a host-shareable struct has no rust representation
+#[macro_export]
+macro_rules! include_types {
+    ($path:literal) => { $crate::__include_types!($crate, $path); };
+}
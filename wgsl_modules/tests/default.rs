@@ -169,4 +169,35 @@ fn naga_validation_failing() {
     });
 
     assert_matches!(res, Err(err) if err.to_string().starts_with("error: Entry point vs_main at Vertex is invalid"));
-}
\ No newline at end of file
+}
+
+mod types {
+    wgsl_modules::include_types!("shaders/types.wgsl");
+}
+
+#[test]
+fn including_types() {
+
+    use std::mem::{size_of, offset_of};
+    use types::*;
+
+    tokens_eq!(SOURCE, include_str!("shaders/types.wgsl"));
+
+    assert_eq!(size_of::<Light>(), 32);
+    assert_eq!((offset_of!(Light, intensity), offset_of!(Light, color)), (12, 16));
+
+    assert_eq!(size_of::<Uniforms>(), 224);
+    assert_eq!(offset_of!(Uniforms, normal_matrix), 64);
+    assert_eq!((offset_of!(Uniforms, lights), offset_of!(Uniforms, offsets), offset_of!(Uniforms, time)), (112, 176, 208));
+
+    // the runtime sized array is left out
+    assert_eq!(size_of::<Particles>(), 16);
+
+    let uniforms: Uniforms = wgsl_modules::bytemuck::Zeroable::zeroed();
+    assert_eq!(wgsl_modules::bytemuck::bytes_of(&uniforms).len(), 224);
+
+    assert_eq!((bindings::UNIFORMS_GROUP, bindings::UNIFORMS_BINDING), (0, 0));
+    assert_eq!((bindings::PARTICLES_GROUP, bindings::PARTICLES_BINDING), (1, 2));
+    assert_eq!(entry_points::CS_MAIN, "cs_main");
+    assert_eq!((overrides::BLEND_FACTOR, overrides::SAMPLES), ("blendFactor", "7"));
+}
@@ -0,0 +1,31 @@
+struct Light {
+    position: vec3f,
+    intensity: f32,
+    color: vec3f,
+};
+
+struct Uniforms {
+    transform: mat4x4f,
+    normal_matrix: mat3x3f,
+    lights: array<Light, 2>,
+    offsets: array<vec3f, 2>,
+    time: f32,
+};
+
+struct Particles {
+    count: u32,
+    items: array<vec4f>,
+};
+
+override blendFactor: f32 = 0.5;
+@id(7) override samples: u32 = 4;
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(1) @binding(2) var<storage, read_write> particles: Particles;
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if id.x < particles.count {
+        particles.items[id.x] *= uniforms.time * blendFactor * f32(samples) + uniforms.lights[0].intensity;
+    }
+}